@@ -14,21 +14,28 @@
  You should have received a copy of the GNU General Public License
  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use std::path::Path;
-
 use protocol_types::{CallData, Serializable};
 use serde_json::{Value};
 use borsh::{BorshSerialize, BorshDeserialize};
 use regex::Regex;
 
+use crate::{file_format::FileFormat, input};
+
 pub struct Builder {
     pub args :Vec<Vec<u8>>
 }
 
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Builder {
     pub fn new() -> Self {
         Self { args: vec![] }
     }
+    #[allow(clippy::should_implement_trait)]
     pub fn add<T: BorshSerialize>(mut self,  arg :T) -> Self{
         self.insert(arg);
         self
@@ -41,7 +48,7 @@ impl Builder {
     }
 
     pub fn make_data(&self, entry_name :&str) -> (Vec<u8>, Vec<u8>) {
-        let version_bs = (0 as u32).to_le_bytes().to_vec();
+        let version_bs = 0_u32.to_le_bytes().to_vec();
 
         let mut args_bs :Vec<u8> = vec![];
         BorshSerialize::serialize(&self.args, &mut args_bs).unwrap();
@@ -195,7 +202,7 @@ impl Builder {
             }
         }
 
-        Ok(())
+        Err(format!("unsupported data type {}", data_type))
     }
 }
 
@@ -206,71 +213,129 @@ struct CLICallData{
 }
 
 impl CLICallData {
-    fn from_json(json_data: &str) -> core::result::Result<CLICallData, ()> {
+    fn from_json(json_data: &str) -> core::result::Result<CLICallData, String> {
+        const E_MSG_FORMAT: &str = "Calldata file must contain a `method_name` string and an `arguments` array of {\"type\": .., \"value\": ..} objects.";
         let json_val: Value = match serde_json::from_str(json_data) {
             Ok(val) => { val },
-            Err(_) => return Err(())
+            Err(_) => return Err(E_MSG_FORMAT.to_string())
         };
 
         // parse method name
         let method_name = match &json_val["method_name"].as_str() {
             Some(method_name) => { method_name.to_string()},
-            None => return Err(())
+            None => return Err(E_MSG_FORMAT.to_string())
         };
 
+        let arguments = Self::arguments_from_json(&json_val)?;
+        
+        Ok(CLICallData{
+            method_name,
+            arguments
+        })
+    }
+
+    fn arguments_from_json(json_val: &Value) -> core::result::Result<Vec<(String, String)>, String> {
         let json_args: Vec<Value> = match &json_val["arguments"].as_array() {
             Some(args) => { args.to_vec() },
-            None => return Err(()),
+            None => return Err("Arguments file must contain an `arguments` array of {\"type\": .., \"value\": ..} objects.".to_string()),
         };
 
         // parse arguments
-        let arguments: Vec<(String, String)> = json_args.iter().filter_map(|jarg|{
+        let mut arguments: Vec<(String, String)> = vec![];
+        for jarg in &json_args {
             if let Some(j_type) = jarg["type"].as_str() {
-                if let Some(j_val) = jarg["value"].as_str() {
-                    Some((j_type.to_string(), j_val.to_string()))
-                } else {None}
-            } else {None}
-        }).collect();
-        
-        Ok(CLICallData{
-            method_name,
-            arguments
-        })
+                // yaml and toml files may give numbers and booleans without quotes. Numbers beyond 64 bits are read
+                // as floating point numbers and lose precision, so 128-bit values must be quoted.
+                match &jarg["value"] {
+                    Value::String(j_val) => arguments.push((j_type.to_string(), j_val.to_string())),
+                    Value::Number(j_val) if j_type.contains("128") => {
+                        return Err(format!("value {} of type {} must be quoted, e.g. \"{}\", as numbers without quotes are limited to 64 bits.", j_val, j_type, j_val));
+                    },
+                    j_val @ (Value::Number(_) | Value::Bool(_)) => arguments.push((j_type.to_string(), j_val.to_string())),
+                    _ => {},
+                }
+            }
+        }
+
+        Ok(arguments)
     }
 }
 
 /// Parse to tx-data and viewargs
 pub fn parse(path_to_json: String) -> (String, String) {
-    let json_string = if Path::new(&path_to_json).is_file(){
-        match std::fs::read(&path_to_json) {
-            Ok(data) => match String::from_utf8(data) {
-                Ok(call_data_json) => {  call_data_json },
-                Err(e) => {
-                    println!("Error: : Fail to parse file although is file found {:?}", e);
-                    std::process::exit(1);  
-                }
-            },
+    let json_val = read_data_file(path_to_json);
+    parse_call_data(json_val.to_string())
+}
+
+/// Parse only the `arguments` array of a calldata json file into type-value pairs. 
+/// The `method_name` field is not required, as the method is given separately.
+pub fn parse_arguments(path_to_json: String) -> Vec<(String, String)> {
+    let json_val = read_data_file(path_to_json);
+    match CLICallData::arguments_from_json(&json_val) {
+        Ok(arguments) => arguments,
+        Err(e) => {
+            println!("Error: : {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a single argument given in the form `type:value`, e.g. `u64:100` or `Vec<u8>:[0,1,2]`.
+pub fn parse_typed_arg(arg: &str) -> (String, String) {
+    match arg.split_once(':') {
+        Some((data_type, value)) if !data_type.trim().is_empty() => (data_type.trim().to_string(), value.to_string()),
+        _ => {
+            println!("Error: : Invalid argument `{}`. Arguments must be given in the form `type:value`, e.g. `u64:100`.", arg);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Build the calldata for calling `method_name` with the given type-value pairs.
+/// Returns the same (data, arguments) pair as `Builder::make_data`.
+pub fn make_call_data(method_name: &str, arguments: Vec<(String, String)>) -> (Vec<u8>, Vec<u8>) {
+    let mut arg_builder = Builder::new();
+    for (index, (data_type, value)) in arguments.iter().enumerate() {
+        if let Err(e) = arg_builder.insert_from_str(data_type.as_str(), value.as_str()) {
+            println!("Error: : Argument {} ({}: {}): {}", index, data_type, value, e);
+            std::process::exit(1);
+        }
+    }
+    arg_builder.make_data(method_name)
+}
+
+// Read data file reads a json, yaml or toml file (or standard input if the path is `-`) into a json value.
+fn read_data_file(path_to_file: String) -> Value {
+    let content = if input::exists(&path_to_file){
+        match input::read_to_string(&path_to_file) {
+            Ok(content) => content,
             Err(e) => {
                 println!("Error: : Fail to read file although is file found {:?}", e);
                 std::process::exit(1);  
             }
         }
     } else {
-        println!("Error: : Invalid path. Cannot retrieve designated keypair file from the designated path.");
+        println!("Error: : Invalid path. Cannot retrieve designated file from the designated path.");
         std::process::exit(1);
     };
-
-    parse_call_data(json_string)
+    match FileFormat::detect(&path_to_file, &content).parse(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            println!("Error: : {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn parse_call_data(json_string: String) -> (String, String) {
-    let call_data_from_json =  CLICallData::from_json(&json_string).unwrap();
-    let mut arg_builder = Builder::new();
-    for (data_type, value) in call_data_from_json.arguments {
-        arg_builder.insert_from_str(data_type.as_str(), value.as_str()).unwrap();
-    }
-
-    let (data, arguments) = arg_builder.make_data(call_data_from_json.method_name.as_str());
+    let call_data_from_json = match CLICallData::from_json(&json_string) {
+        Ok(call_data) => call_data,
+        Err(e) => {
+            println!("Error: : {}", e);
+            std::process::exit(1);
+        }
+    };
+    let (data, arguments) = make_call_data(call_data_from_json.method_name.as_str(), call_data_from_json.arguments);
     let output_data_str = protocol_types::Base64URL::encode(data).to_string();
     let output_arguments_str = protocol_types::Base64URL::encode(arguments).to_string();
     (output_data_str, output_arguments_str)
@@ -283,17 +348,26 @@ struct CallBack {
 
 impl CallBack {
     fn to_data_type(&self, data_type: String) -> String {
+        match self.try_to_data_type(&data_type) {
+            Ok(data) => data,
+            Err(e) => {
+                println!("Error: : {}.", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn try_to_data_type(&self, data_type: &str) -> Result<String, String> {
         macro_rules! convert_to_data_type {
             ($d:expr, $($t:ty,)*) => {
                 $(
                     if data_type == stringify!($t) {
                         match <$t>::deserialize(&mut self.return_value.as_slice()) {
                             Ok(data) => {
-                                return format!("{:?}", data);
+                                return Ok(format!("{:?}", data));
                             },
                             Err(e) => {
-                                println!("Error: : Fail to convert to target data tyoe. {:?}", e);
-                                std::process::exit(1);  
+                                return Err(format!("Fail to convert to target data type. {:?}", e));
                             }
                         }
                     }
@@ -307,11 +381,10 @@ impl CallBack {
                     if data_type == concat!("Vec<", stringify!($t), ">") {
                         match Vec::<$t>::deserialize(&mut self.return_value.as_slice()) {
                             Ok(data) => {
-                                return format!("{:?}", data);
+                                return Ok(format!("{:?}", data));
                             },
                             Err(e) => {
-                                println!("Error: : Fail to convert to target data tyoe. {:?}", e);
-                                std::process::exit(1);  
+                                return Err(format!("Fail to convert to target data type. {:?}", e));
                             }
                         }
                     }
@@ -325,11 +398,10 @@ impl CallBack {
                     if data_type == concat!("[", stringify!($s), "]") {
                         match <[u8; $s]>::deserialize(&mut self.return_value.as_slice()) {
                             Ok(data) => {
-                                return format!("{:?}", data);
+                                return Ok(format!("{:?}", data));
                             },
                             Err(e) => {
-                                println!("Error: : Fail to convert to target data tyoe. {:?}", e);
-                                std::process::exit(1);  
+                                return Err(format!("Fail to convert to target data type. {:?}", e));
                             }
                         }
                     }
@@ -353,7 +425,22 @@ impl CallBack {
             32, 64,
         );
 
-        "".to_string()
+        Err(format!("Unsupported data type `{}`", data_type))
+    }
+}
+
+/// Decode Borsh serialized bytes (e.g. an event value) as the given data type. 
+/// Accept the same data types as `from_callback`.
+pub fn decode_borsh_value(value: &[u8], data_type: &str) -> Result<String, String> {
+    CallBack { return_value: value.to_vec() }.try_to_data_type(data_type)
+}
+
+/// Extract the raw return value bytes from the Borsh serialized callback of a contract call.
+/// Returns None if the bytes are not a callback.
+pub fn callback_return_value(value: &[u8]) -> Option<Vec<u8>> {
+    match CallBack::try_from_slice(value) {
+        Ok(call_back) => Some(call_back.return_value),
+        Err(_) => None
     }
 }
 
@@ -382,6 +469,8 @@ pub fn from_callback(value :String, data_type: String) -> String {
 mod test {
     use borsh::BorshSerialize;
 
+    use crate::file_format::FileFormat;
+
     use super::CallBack;
 
 
@@ -424,6 +513,47 @@ mod test {
         assert_eq!(p, "GgAAAAEAAAD_BQAAAAEAAAD_AgAAANCKCAAAAAIAAAD__wAABAAAAAEgvL4QAAAAAwAAAP____8AAAAAAQAAAAgAAAABAKxYMoJzgiQAAAAEAAAA__________8AAAAAAAAAAAEAAAAAAAAA9dbpAwAAAAAQAAAAAQCsWDKCc4L__________1QAAAAFAAAA_____________________wAAAAAAAAAAAAAAAAAAAAABAAAAAAAAAAAAAAAAAAAACykW_P_______________wAAAAAAAAAAAAAAAAAAAAABAAAA_wUAAAABAAAAAAIAAAD__wgAAAACAAAA__-HGQQAAAD_____EAAAAAMAAAD__wAAhxkAAH8HAAAIAAAA__________8cAAAAAwAAAP__AAAAAAAAhxkAAAAAAABXE__TvgEAABAAAAAAAAAAAAAAAAEAAAAAAAAAZAAAAAYAAAD__wAAAAAAAAAAAAAAAAAAhxkAAAAAAAAAAAAAAAAAAFcT_9O-AQAAAAAAAAAAAADz4AEAAAAAAAAAAAAAAAAA3OQBAAAAAAAAAAAAAAAAAODoAQAAAAAAAAAAAAAAAAABAAAAAQcAAAADAAAAAQABDwAAAAsAAABzdHJpbmcgZGF0YScAAAADAAAACwAAAHN0cmluZyBkYXRhBQAAAGFzZGFmBwAAADFkMSBhczIgAAAAAQIDBAUGBwgJAAECAwQFBgcICQABAgMEBQYHCAkAAQJAAAAAAQIDBAUGBwgJAAECAwQFBgcICQABAgMEBQYHCAkAAQIBAgMEBQYHCAkAAQIDBAUGBwgJAAECAwQFBgcICQABAg");
     }
 
+    #[test]
+    fn test_make_call_data() {
+        let json_string = r#" {
+            "method_name": "transfer",
+            "arguments": [
+                {"type": "u64", "value":"100"},
+                {"type": "Vec<u8>", "value":"[0,1,2]"},
+                {"type": "String", "value": "a:b"}
+            ]
+        }"#;
+        let (expected_data, expected_args) = super::parse_call_data(json_string.to_string());
+
+        let arguments = ["u64:100", "Vec<u8>:[0,1,2]", "String:a:b"].iter().map(|a| super::parse_typed_arg(a)).collect();
+        let (data, args) = super::make_call_data("transfer", arguments);
+        assert_eq!(protocol_types::Base64URL::encode(data).to_string(), expected_data);
+        assert_eq!(protocol_types::Base64URL::encode(args).to_string(), expected_args);
+
+        let mut builder = super::Builder::new();
+        assert!(builder.insert_from_str("f64", "1.0").is_err());
+    }
+
+    #[test]
+    fn test_arguments_from_json() {
+        let yaml = "arguments:\n  - type: u64\n    value: 18446744073709551615\n  - type: bool\n    value: true\n  - type: u128\n    value: \"340282366920938463463374607431768211455\"\n";
+        let arguments = super::CLICallData::arguments_from_json(&FileFormat::Yaml.parse(yaml).unwrap()).unwrap();
+        assert_eq!(arguments, vec![
+            ("u64".to_string(), "18446744073709551615".to_string()),
+            ("bool".to_string(), "true".to_string()),
+            ("u128".to_string(), "340282366920938463463374607431768211455".to_string()),
+        ]);
+
+        // Unquoted numbers beyond 64 bits lose precision, so they are rejected for 128-bit types.
+        let yaml = "arguments:\n  - type: u128\n    value: 5\n";
+        let e = super::CLICallData::arguments_from_json(&FileFormat::Yaml.parse(yaml).unwrap()).unwrap_err();
+        assert!(e.contains("u128") && e.contains("quoted"));
+        let toml = "[[arguments]]\ntype = \"i128\"\nvalue = -1\n";
+        assert!(super::CLICallData::arguments_from_json(&FileFormat::Toml.parse(toml).unwrap()).is_err());
+        let json = serde_json::json!({ "arguments": [{ "type": "Vec<u128>", "value": 1 }] });
+        assert!(super::CLICallData::arguments_from_json(&json).is_err());
+    }
+
     #[test]
     fn test_callback(){
         macro_rules! assert_data_types {
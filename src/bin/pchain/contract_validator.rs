@@ -0,0 +1,285 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+// Contract validator checks a compiled Smart Contract before it is deployed.
+// It only reads the module structure (header, sections, imports and exports), so that
+// obviously broken contracts are rejected locally instead of burning gas on-chain.
+
+use std::fmt;
+
+const WASM_MAGIC: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+const WASM_VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
+
+/// Largest contract (in bytes) VeryLight is willing to deploy.
+pub const MAX_CONTRACT_SIZE: usize = 4 * 1024 * 1024;
+
+/// Contracts may only import host functions provided by the ParallelChain runtime.
+const ALLOWED_IMPORT_MODULE: &str = "env";
+
+/// Entrypoint called by Transactions sent to the Contract.
+const CALL_ENTRYPOINT: &str = "actions";
+/// Entrypoint called by `query account view`.
+const VIEW_ENTRYPOINT: &str = "views";
+/// Entrypoint called with the deploy arguments on deployment.
+const INIT_ENTRYPOINT: &str = "init";
+
+const SECTION_IMPORT: u8 = 2;
+const SECTION_EXPORT: u8 = 7;
+const MAX_SECTION_ID: u8 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExternalKind {
+    Function,
+    Table,
+    Memory,
+    Global,
+}
+
+impl fmt::Display for ExternalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ExternalKind::Function => "function",
+            ExternalKind::Table => "table",
+            ExternalKind::Memory => "memory",
+            ExternalKind::Global => "global",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+#[derive(Debug)]
+pub struct ContractSummary {
+    pub size: usize,
+    pub imports: Vec<(String, String, ExternalKind)>,
+    pub exports: Vec<(String, ExternalKind)>,
+}
+
+impl ContractSummary {
+    fn exports_function(&self, name: &str) -> bool {
+        self.exports.iter().any(|(export_name, kind)| export_name == name && *kind == ExternalKind::Function)
+    }
+}
+
+impl fmt::Display for ContractSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Contract size: {} bytes", self.size)?;
+        writeln!(f, "Contract imports ({}):", self.imports.len())?;
+        for (module, name, kind) in &self.imports {
+            writeln!(f, "    {}::{} ({})", module, name, kind)?;
+        }
+        write!(f, "Contract exports ({}):", self.exports.len())?;
+        for (name, kind) in &self.exports {
+            write!(f, "\n    {} ({})", name, kind)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate the bytes of a compiled Smart Contract. `with_init_args` should be set if
+/// arguments are passed to the "init" entrypoint, in which case the entrypoint must exist.
+pub fn validate(code: &[u8], with_init_args: bool) -> Result<ContractSummary, String> {
+    if code.len() > MAX_CONTRACT_SIZE {
+        return Err(format!("contract is {} bytes, which exceeds the limit of {} bytes", code.len(), MAX_CONTRACT_SIZE));
+    }
+    if code.len() < 8 || code[0..4] != WASM_MAGIC {
+        return Err("file is not a WebAssembly module (missing `\\0asm` magic number)".to_string());
+    }
+    if code[4..8] != WASM_VERSION {
+        return Err(format!("unsupported WebAssembly version {:?}", &code[4..8]));
+    }
+
+    let mut summary = ContractSummary {
+        size: code.len(),
+        imports: vec![],
+        exports: vec![],
+    };
+
+    let mut reader = Reader { bytes: code, pos: 8 };
+    while !reader.is_empty() {
+        let section_id = reader.byte()?;
+        if section_id > MAX_SECTION_ID {
+            return Err(format!("unknown section id {} at offset {}", section_id, reader.pos - 1));
+        }
+        let section_size = reader.leb_u32()? as usize;
+        let section_bytes = reader.take(section_size)
+            .map_err(|_| format!("section {} is truncated", section_id))?;
+        let mut section = Reader { bytes: section_bytes, pos: 0 };
+        match section_id {
+            SECTION_IMPORT => summary.imports = read_imports(&mut section)?,
+            SECTION_EXPORT => summary.exports = read_exports(&mut section)?,
+            _ => {}
+        }
+    }
+
+    for (module, name, _) in &summary.imports {
+        if module != ALLOWED_IMPORT_MODULE {
+            return Err(format!("disallowed import `{}::{}`. Contracts may only import from `{}`", module, name, ALLOWED_IMPORT_MODULE));
+        }
+    }
+    if !summary.exports.iter().any(|(_, kind)| *kind == ExternalKind::Memory) {
+        return Err("contract does not export its memory".to_string());
+    }
+    if !summary.exports_function(CALL_ENTRYPOINT) && !summary.exports_function(VIEW_ENTRYPOINT) {
+        return Err(format!("contract exports neither the `{}` nor the `{}` entrypoint", CALL_ENTRYPOINT, VIEW_ENTRYPOINT));
+    }
+    if with_init_args && !summary.exports_function(INIT_ENTRYPOINT) {
+        return Err(format!("deploy arguments are given but the contract does not export the `{}` entrypoint", INIT_ENTRYPOINT));
+    }
+
+    Ok(summary)
+}
+
+fn read_imports(section: &mut Reader) -> Result<Vec<(String, String, ExternalKind)>, String> {
+    let count = section.leb_u32()?;
+    let mut imports = vec![];
+    for _ in 0..count {
+        let module = section.name()?;
+        let name = section.name()?;
+        let kind = match section.byte()? {
+            0x00 => { section.leb_u32()?; ExternalKind::Function },
+            0x01 => { section.byte()?; section.limits()?; ExternalKind::Table },
+            0x02 => { section.limits()?; ExternalKind::Memory },
+            0x03 => { section.byte()?; section.byte()?; ExternalKind::Global },
+            kind => return Err(format!("unknown import kind {} for `{}::{}`", kind, module, name)),
+        };
+        imports.push((module, name, kind));
+    }
+    Ok(imports)
+}
+
+fn read_exports(section: &mut Reader) -> Result<Vec<(String, ExternalKind)>, String> {
+    let count = section.leb_u32()?;
+    let mut exports = vec![];
+    for _ in 0..count {
+        let name = section.name()?;
+        let kind = match section.byte()? {
+            0x00 => ExternalKind::Function,
+            0x01 => ExternalKind::Table,
+            0x02 => ExternalKind::Memory,
+            0x03 => ExternalKind::Global,
+            kind => return Err(format!("unknown export kind {} for `{}`", kind, name)),
+        };
+        section.leb_u32()?;
+        exports.push((name, kind));
+    }
+    Ok(exports)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        let b = *self.bytes.get(self.pos).ok_or_else(|| "unexpected end of module".to_string())?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.pos < len {
+            return Err("unexpected end of module".to_string());
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn leb_u32(&mut self) -> Result<u32, String> {
+        let mut result: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let b = self.byte()?;
+            result |= ((b & 0x7f) as u32) << shift;
+            if b & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Err("malformed LEB128 integer".to_string())
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let len = self.leb_u32()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "name is not valid utf8".to_string())
+    }
+
+    fn limits(&mut self) -> Result<(), String> {
+        match self.byte()? {
+            0x00 => { self.leb_u32()?; },
+            0x01 => { self.leb_u32()?; self.leb_u32()?; },
+            flag => return Err(format!("unknown limits flag {}", flag)),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExternalKind;
+
+    fn section(id: u8, content: Vec<u8>) -> Vec<u8> {
+        [vec![id, content.len() as u8], content].concat()
+    }
+
+    fn name(s: &str) -> Vec<u8> {
+        [vec![s.len() as u8], s.as_bytes().to_vec()].concat()
+    }
+
+    fn module(import_module: &str, exports: &[(&str, u8)]) -> Vec<u8> {
+        let header = [super::WASM_MAGIC.to_vec(), super::WASM_VERSION.to_vec()].concat();
+        let imports = section(2, [vec![1], name(import_module), name("get"), vec![0x00, 0x00]].concat());
+        let mut export_content = vec![exports.len() as u8];
+        for (export_name, kind) in exports {
+            export_content.extend(name(export_name));
+            export_content.extend([*kind, 0x00]);
+        }
+        let custom = section(0, name("producers"));
+        [header, custom, imports, section(7, export_content)].concat()
+    }
+
+    #[test]
+    fn test_validate() {
+        let code = module("env", &[("memory", 0x02), ("actions", 0x00), ("init", 0x00)]);
+        let summary = super::validate(&code, true).unwrap();
+        assert_eq!(summary.size, code.len());
+        assert_eq!(summary.imports, vec![("env".to_string(), "get".to_string(), ExternalKind::Function)]);
+        assert_eq!(summary.exports.len(), 3);
+
+        let code = module("env", &[("memory", 0x02), ("views", 0x00)]);
+        assert!(super::validate(&code, false).is_ok());
+        assert!(super::validate(&code, true).unwrap_err().contains("init"));
+
+        let code = module("wasi_snapshot_preview1", &[("memory", 0x02), ("actions", 0x00)]);
+        assert!(super::validate(&code, false).unwrap_err().contains("disallowed import"));
+
+        let code = module("env", &[("actions", 0x00)]);
+        assert!(super::validate(&code, false).unwrap_err().contains("memory"));
+
+        let code = module("env", &[("memory", 0x02)]);
+        assert!(super::validate(&code, false).unwrap_err().contains("entrypoint"));
+
+        assert!(super::validate(b"#!/bin/sh\necho", false).unwrap_err().contains("magic"));
+
+        let mut code = module("env", &[("memory", 0x02), ("actions", 0x00)]);
+        code.truncate(code.len() - 2);
+        assert!(super::validate(&code, false).is_err());
+    }
+}
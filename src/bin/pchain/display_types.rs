@@ -16,12 +16,167 @@
  */
 // Displayed Types module translate original raw data types: Transaction/ Block to human readble form.
 // For example, data which are originally in bytes will be displayed in base64 encoded string.
+// The types of Prt files can also be translated back, so that Prt files can be written from their displayed form.
+
+use std::{collections::BTreeMap, convert::{TryFrom, TryInto}, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{Base64String};
+use crate::{Base64String, stats::Distribution, time_parser};
+
+// Bytes encoding selects how free-form bytes (Event topics and values, Receipt return values) are displayed.
+// Contracts may put arbitrary bytes there, so they cannot be assumed to be utf8.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BytesEncoding {
+    /// utf8 string. Invalid sequences are replaced with U+FFFD.
+    Utf8,
+    Hex,
+    Base64,
+    /// utf8 string if the bytes are valid utf8, Base64 otherwise.
+    Auto,
+}
 
-#[derive(Debug)]
+impl FromStr for BytesEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utf8" => Ok(BytesEncoding::Utf8),
+            "hex" => Ok(BytesEncoding::Hex),
+            "base64" => Ok(BytesEncoding::Base64),
+            "auto" => Ok(BytesEncoding::Auto),
+            _ => Err(format!("unknown encoding `{}`. Expected one of utf8, hex, base64, auto", s)),
+        }
+    }
+}
+
+impl BytesEncoding {
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            BytesEncoding::Utf8 => String::from_utf8_lossy(bytes).to_string(),
+            BytesEncoding::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            BytesEncoding::Base64 => protocol_types::Base64URL::encode(bytes).to_string(),
+            BytesEncoding::Auto => match std::str::from_utf8(bytes) {
+                Ok(s) => s.to_string(),
+                Err(_) => protocol_types::Base64URL::encode(bytes).to_string(),
+            },
+        }
+    }
+
+    // Decode is the inverse of encode. Auto cannot be decoded, because utf8 strings may also be valid Base64.
+    pub fn decode(&self, s: &str) -> Result<Vec<u8>, String> {
+        if s.is_empty() {
+            return Ok(vec![]);
+        }
+        match self {
+            BytesEncoding::Utf8 => Ok(s.as_bytes().to_vec()),
+            BytesEncoding::Hex => s.as_bytes().chunks(2).map(|pair| {
+                std::str::from_utf8(pair).ok()
+                    .filter(|pair| pair.len() == 2 && pair.chars().all(|c| c.is_ascii_hexdigit()))
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| format!("`{}` is not hex encoded", s))
+            }).collect(),
+            BytesEncoding::Base64 => protocol_types::Base64URL::decode(s).map_err(|e| format!("`{}` is not Base64URL encoded. {:?}", s, e)),
+            BytesEncoding::Auto => Err(format!("`{}` cannot be decoded with encoding auto. It must be utf8, hex or base64", s)),
+        }
+    }
+}
+
+// Encoded from translates a protocol type into its display type, with free-form bytes displayed in `encoding`.
+// It is implemented by every display type of a protocol type, so that they can be translated alike.
+pub trait EncodedFrom<T> {
+    fn encoded_from(value: T, encoding: BytesEncoding) -> Self;
+}
+
+// Display types without free-form bytes are displayed the same in every encoding.
+macro_rules! encoded_from_ignoring_encoding {
+    ($($t:ty => $d:ty),*) => {
+        $(
+            impl EncodedFrom<$t> for $d {
+                fn encoded_from(value: $t, _: BytesEncoding) -> Self {
+                    Self::from(value)
+                }
+            }
+        )*
+    };
+}
+
+encoded_from_ignoring_encoding!(
+    protocol_types::transaction::Transaction => Transaction,
+    protocol_types::block::BlockHeader => BlockHeader,
+    protocol_types::sc_params::CallData => CallData,
+    protocol_types::MerkleProof => MerkleProof,
+    protocol_types::StateProofs => StateProofs
+);
+
+// Decoded from is the inverse of encoded from. It translates a display type back into its protocol type, with
+// free-form bytes decoded from `encoding`.
+pub trait DecodedFrom<T>: Sized {
+    fn decoded_from(value: T, encoding: BytesEncoding) -> Result<Self, String>;
+}
+
+macro_rules! decoded_from_ignoring_encoding {
+    ($($d:ty => $t:ty),*) => {
+        $(
+            impl DecodedFrom<$d> for $t {
+                fn decoded_from(value: $d, _: BytesEncoding) -> Result<Self, String> {
+                    Self::try_from(value)
+                }
+            }
+        )*
+    };
+}
+
+decoded_from_ignoring_encoding!(
+    Transaction => protocol_types::transaction::Transaction,
+    BlockHeader => protocol_types::block::BlockHeader,
+    CallData => protocol_types::sc_params::CallData,
+    MerkleProof => protocol_types::MerkleProof,
+    StateProofs => protocol_types::StateProofs
+);
+
+// Serialize debug serializes protocol types without a serde representation by their Debug name.
+fn serialize_debug<T: std::fmt::Debug, S: serde::Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:?}", value))
+}
+
+// Deserialize receipt status code reads a status code serialized by its Debug name.
+fn deserialize_receipt_status_code<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<protocol_types::receipt_status_codes::ReceiptStatusCode, D::Error> {
+    use protocol_types::receipt_status_codes::ReceiptStatusCode;
+    let name = String::deserialize(deserializer)?;
+    Ok(match name.as_str() {
+        "Success" => ReceiptStatusCode::Success,
+        "WrongNonce" => ReceiptStatusCode::WrongNonce,
+        "NotEnoughBalanceForGasLimit" => ReceiptStatusCode::NotEnoughBalanceForGasLimit,
+        "NotEnoughBalanceForTransfer" => ReceiptStatusCode::NotEnoughBalanceForTransfer,
+        "PreExecutionGasExhausted" => ReceiptStatusCode::PreExecutionGasExhausted,
+        "DisallowedOpcode" => ReceiptStatusCode::DisallowedOpcode,
+        "CannotCompile" => ReceiptStatusCode::CannotCompile,
+        "NoExportedContractMethod" => ReceiptStatusCode::NoExportedContractMethod,
+        "OtherDeployError" => ReceiptStatusCode::OtherDeployError,
+        "ExecutionProperGasExhausted" => ReceiptStatusCode::ExecutionProperGasExhausted,
+        "Reverted" => ReceiptStatusCode::Reverted,
+        "InternalExecutionProperGasExhaustion" => ReceiptStatusCode::InternalExecutionProperGasExhaustion,
+        "InternalReverted" => ReceiptStatusCode::InternalReverted,
+        "Else" => ReceiptStatusCode::Else,
+        _ => return Err(serde::de::Error::custom(format!("unknown status code `{}`", name))),
+    })
+}
+
+// Deserialize timestamp reads a unix timestamp in seconds as an ISO date, in the same way as BlockHeader timestamps are displayed.
+fn deserialize_timestamp<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(time_parser::format_timestamp(u32::deserialize(deserializer)? as u64))
+}
+
+fn decode_base64(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    protocol_types::Base64URL::decode(value).map_err(|e| format!("'{}' is not Base64URL encoded. {:?}", field, e))
+}
+
+fn decode_base64_array<const N: usize>(field: &str, value: &str) -> Result<[u8; N], String> {
+    decode_base64(field, value)?.try_into().map_err(|bytes: Vec<u8>| format!("'{}' must be {} bytes, but is {} bytes", field, N, bytes.len()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Transaction {
     pub from_address: String,
     pub to_address: String,
@@ -52,12 +207,31 @@ impl From<protocol_types::transaction::Transaction> for Transaction {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<Transaction> for protocol_types::transaction::Transaction {
+    type Error = String;
+
+    fn try_from(transaction: Transaction) -> Result<Self, String> {
+        Ok(protocol_types::transaction::Transaction {
+            from_address: decode_base64_array("from_address", &transaction.from_address)?,
+            to_address: decode_base64_array("to_address", &transaction.to_address)?,
+            value: transaction.value,
+            tip: transaction.tip,
+            gas_limit: transaction.gas_limit,
+            gas_price: transaction.gas_price,
+            data: decode_base64("data", &transaction.data)?,
+            n_txs_on_chain_from_address: transaction.n_txs_on_chain_from_address,
+            hash: decode_base64_array("hash", &transaction.hash)?,
+            signature: decode_base64_array("signature", &transaction.signature)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockHeader {
     pub blockchain_id: u64,
     pub block_version_number: u64,
     pub block_number: u64,
-    pub timestamp: u32,
+    pub timestamp: String,
     pub prev_block_hash: String,
     pub this_block_hash: String,
     pub txs_hash: String,
@@ -73,7 +247,7 @@ impl From<protocol_types::block::BlockHeader> for BlockHeader {
             blockchain_id: blockheader.blockchain_id,
             block_version_number: blockheader.block_version_number,
             block_number: blockheader.block_number,
-            timestamp: blockheader.timestamp,
+            timestamp: time_parser::format_timestamp(blockheader.timestamp as u64),
             prev_block_hash: protocol_types::Base64URL::encode(blockheader.prev_block_hash).to_string(),
             this_block_hash: protocol_types::Base64URL::encode(blockheader.this_block_hash).to_string(),
             txs_hash: protocol_types::Base64URL::encode(blockheader.txs_hash).to_string(),
@@ -85,67 +259,108 @@ impl From<protocol_types::block::BlockHeader> for BlockHeader {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<BlockHeader> for protocol_types::block::BlockHeader {
+    type Error = String;
+
+    fn try_from(blockheader: BlockHeader) -> Result<Self, String> {
+        let timestamp = time_parser::parse_timestamp(&blockheader.timestamp).map_err(|e| format!("'timestamp' is invalid. {}", e))?;
+        Ok(protocol_types::block::BlockHeader {
+            blockchain_id: blockheader.blockchain_id,
+            block_version_number: blockheader.block_version_number,
+            block_number: blockheader.block_number,
+            timestamp: timestamp.try_into().map_err(|_| format!("'timestamp' {} is out of range", blockheader.timestamp))?,
+            prev_block_hash: decode_base64_array("prev_block_hash", &blockheader.prev_block_hash)?,
+            this_block_hash: decode_base64_array("this_block_hash", &blockheader.this_block_hash)?,
+            txs_hash: decode_base64_array("txs_hash", &blockheader.txs_hash)?,
+            state_hash: decode_base64_array("state_hash", &blockheader.state_hash)?,
+            receipts_hash: decode_base64_array("receipts_hash", &blockheader.receipts_hash)?,
+            proposer_public_key: decode_base64_array("proposer_public_key", &blockheader.proposer_public_key)?,
+            signature: decode_base64_array("signature", &blockheader.signature)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Event {
     pub topic: String,
     pub value: String
 }
-impl From<protocol_types::transaction::Event> for Event {
-    fn from(event: protocol_types::transaction::Event) -> Event {
+impl EncodedFrom<protocol_types::transaction::Event> for Event {
+    fn encoded_from(event: protocol_types::transaction::Event, encoding: BytesEncoding) -> Event {
         Event {
-            topic: String::from_utf8(event.topic).unwrap(),
-            value: String::from_utf8(event.value).unwrap(),
+            topic: encoding.encode(&event.topic),
+            value: encoding.encode(&event.value),
         }
     }
 }
 
-#[derive(Debug)]
+impl DecodedFrom<Event> for protocol_types::transaction::Event {
+    fn decoded_from(event: Event, encoding: BytesEncoding) -> Result<Self, String> {
+        Ok(protocol_types::transaction::Event {
+            topic: encoding.decode(&event.topic).map_err(|e| format!("'topic' is invalid. {}", e))?,
+            value: encoding.decode(&event.value).map_err(|e| format!("'value' is invalid. {}", e))?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Receipt {
+    #[serde(serialize_with = "serialize_debug", deserialize_with = "deserialize_receipt_status_code")]
     pub status_code: protocol_types::receipt_status_codes::ReceiptStatusCode,
     pub gas_consumed: u64,
-    pub return_value: Vec<u8>,
+    pub return_value: String,
     pub events: Vec<Event>,
 }
 
-impl From<protocol_types::Receipt> for Receipt {
-    fn from(receipt: protocol_types::transaction::Receipt) -> Receipt {
+impl EncodedFrom<protocol_types::Receipt> for Receipt {
+    fn encoded_from(receipt: protocol_types::transaction::Receipt, encoding: BytesEncoding) -> Receipt {
 
         let events_beautified: Vec<Event> = receipt.events.into_iter().map(
             |protocol_types_event|{
-                From::<protocol_types::transaction::Event>::from(protocol_types_event)
+                Event::encoded_from(protocol_types_event, encoding)
         }).collect();
         Receipt {
             status_code: receipt.status_code,
             gas_consumed: receipt.gas_consumed,
-            return_value: receipt.return_value,
+            return_value: encoding.encode(&receipt.return_value),
             events: events_beautified,
         }
     }
 }
 
-#[derive(Debug)]
+impl DecodedFrom<Receipt> for protocol_types::transaction::Receipt {
+    fn decoded_from(receipt: Receipt, encoding: BytesEncoding) -> Result<Self, String> {
+        Ok(protocol_types::transaction::Receipt {
+            status_code: receipt.status_code,
+            gas_consumed: receipt.gas_consumed,
+            return_value: encoding.decode(&receipt.return_value).map_err(|e| format!("'return_value' is invalid. {}", e))?,
+            events: receipt.events.into_iter().map(|event| DecodedFrom::decoded_from(event, encoding)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct TransactionWithReceipt {
     pub tx_num: u64,
     pub transaction: Transaction,
     pub receipt: Receipt
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Block {
     pub header: BlockHeader,
     pub transactions: Vec<Transaction>,
     pub receipts: Vec<Receipt>,
 }
 
-impl From<protocol_types::block::Block> for Block {
-    fn from(block: protocol_types::block::Block) -> Block {
+impl EncodedFrom<protocol_types::block::Block> for Block {
+    fn encoded_from(block: protocol_types::block::Block, encoding: BytesEncoding) -> Block {
         let txs_beautified: Vec<Transaction> = block.transactions.into_iter().map(
-            |protocol_type_transaction|
-            From::<protocol_types::transaction::Transaction>::from(protocol_type_transaction)
+            From::<protocol_types::transaction::Transaction>::from
         ).collect();
         let receipt_beautified: Vec<Receipt> = block.receipts.into_iter().map(
             |protocol_type_receipt|
-            From::<protocol_types::transaction::Receipt>::from(protocol_type_receipt)
+            Receipt::encoded_from(protocol_type_receipt, encoding)
         ).collect();
         
         Block {
@@ -156,7 +371,17 @@ impl From<protocol_types::block::Block> for Block {
     }
 }
 
-#[derive(Debug)]
+impl DecodedFrom<Block> for protocol_types::block::Block {
+    fn decoded_from(block: Block, encoding: BytesEncoding) -> Result<Self, String> {
+        Ok(protocol_types::block::Block {
+            header: TryFrom::try_from(block.header)?,
+            transactions: block.transactions.into_iter().map(TryFrom::try_from).collect::<Result<_, _>>()?,
+            receipts: block.receipts.into_iter().map(|receipt| DecodedFrom::decoded_from(receipt, encoding)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CallData {
     pub method_name: String,
     pub arguments: Base64String
@@ -171,7 +396,18 @@ impl From<protocol_types::sc_params::CallData> for CallData {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<CallData> for protocol_types::sc_params::CallData {
+    type Error = String;
+
+    fn try_from(call_data: CallData) -> Result<Self, String> {
+        Ok(protocol_types::sc_params::CallData {
+            method_name: call_data.method_name,
+            arguments: decode_base64("arguments", &call_data.arguments)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MerkleProof {
     pub root_hash : Base64String,
     pub total_leaves_count: usize,
@@ -196,7 +432,21 @@ impl From<protocol_types::MerkleProof> for MerkleProof {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<MerkleProof> for protocol_types::MerkleProof {
+    type Error = String;
+
+    fn try_from(merkle_proof: MerkleProof) -> Result<Self, String> {
+        Ok(protocol_types::MerkleProof {
+            root_hash: decode_base64_array("root_hash", &merkle_proof.root_hash)?,
+            total_leaves_count: merkle_proof.total_leaves_count,
+            leaf_indices: merkle_proof.leaf_indices,
+            leaf_hashes: merkle_proof.leaf_hashes.iter().map(|h| decode_base64_array("leaf_hashes", h)).collect::<Result<_, _>>()?,
+            proof: merkle_proof.proof,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StateProofs {
     pub root_hash: Base64String,
     pub items: Vec<protocol_types::StateProofItem>,
@@ -214,16 +464,28 @@ impl From<protocol_types::StateProofs> for StateProofs {
     }
 }
 
+impl TryFrom<StateProofs> for protocol_types::StateProofs {
+    type Error = String;
+
+    fn try_from(state_proofs: StateProofs) -> Result<Self, String> {
+        Ok(protocol_types::StateProofs {
+            root_hash: decode_base64_array("root_hash", &state_proofs.root_hash)?,
+            items: state_proofs.items,
+            proof: state_proofs.proof,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct Blocks {
     pub blocks: Vec<Block>,
 }
 
-impl From< Vec::<protocol_types::Block> > for Blocks {
-    fn from(block_vec: Vec::<protocol_types::Block>) -> Self {
+impl EncodedFrom< Vec::<protocol_types::Block> > for Blocks {
+    fn encoded_from(block_vec: Vec::<protocol_types::Block>, encoding: BytesEncoding) -> Self {
         let blocks_beautified: Vec<Block> = block_vec.into_iter().map(
             |protocol_type_block|{
-            let block_without_blocknum:Block = From::<protocol_types::Block>::from(protocol_type_block);
+            let block_without_blocknum:Block = Block::encoded_from(protocol_type_block, encoding);
             block_without_blocknum
         }).collect();
         Blocks {
@@ -260,16 +522,16 @@ pub struct TransactionsWithReceipt {
     pub transactions: Vec<TransactionWithReceipt>,
 }
 
-impl From< Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)> > for TransactionsWithReceipt {
+impl EncodedFrom< Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)> > for TransactionsWithReceipt {
     
-    fn from(tx_vector: Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)>) -> TransactionsWithReceipt {
+    fn encoded_from(tx_vector: Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)>, encoding: BytesEncoding) -> TransactionsWithReceipt {
         let mut transactions = vec![];
         
         tx_vector.iter().for_each(|(tx_num, ptype_tx, ptype_recp)|{
             let tx: Transaction = From::<protocol_types::Transaction>::from(ptype_tx.clone());
-            let recp: Receipt = From::<protocol_types::Receipt>::from(ptype_recp.clone());
+            let recp: Receipt = Receipt::encoded_from(ptype_recp.clone(), encoding);
             transactions.push(TransactionWithReceipt{
-                tx_num: tx_num.clone(),
+                tx_num: *tx_num,
                 transaction: tx,
                 receipt: recp
             });
@@ -285,7 +547,8 @@ pub struct BlockSummary {
     pub block_hash: String,
     pub state_hash: String,
     pub receipts_hash: String,
-    pub time: u32,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    pub time: String,
     pub tx_count: u32,
 }
 
@@ -297,4 +560,133 @@ pub struct TxnSummary {
     pub to_address: String,
     pub value: u64,
     pub status_code: u8,
-}
\ No newline at end of file
+}
+#[derive(Debug, Serialize)]
+pub struct EventRecord {
+    pub block_number: u64,
+    pub tx_hash: Base64String,
+    pub contract_address: Base64String,
+    pub topic: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded_value: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReceiptRecord {
+    pub tx_num: u64,
+    pub tx_hash: Base64String,
+    pub receipt: Receipt,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountTxnSummary {
+    pub block_number: u64,
+    pub direction: String,
+    pub hash: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub value: u64,
+    pub status_code: u8,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncludedTransaction {
+    pub tx_num: u64,
+    pub block_number: u64,
+    pub block_hash: Base64String,
+    pub transaction: Transaction,
+    pub receipt: Receipt,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProposerStats {
+    pub proposer_public_key: Base64String,
+    pub blocks: u64,
+    pub share_percent: f64,
+    pub tips: u64,
+    pub first_block: u64,
+    pub last_block: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlotGap {
+    pub block_number: u64,
+    pub prev_timestamp: u32,
+    pub timestamp: u32,
+    pub missed_slots: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProposerReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub blocks: u64,
+    pub slot_time: u64,
+    pub missed_slots: u64,
+    pub proposers: Vec<ProposerStats>,
+    pub gaps: Vec<SlotGap>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThroughputReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub blocks: u64,
+    pub transactions: u64,
+    pub duration_secs: u64,
+    pub tps: f64,
+    pub txs_per_block: Option<Distribution>,
+    pub block_interval_secs: Option<Distribution>,
+    // Number of Blocks produced after each interval (in seconds).
+    pub block_interval_histogram: BTreeMap<u64, u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeesReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub transactions: u64,
+    pub gas_price: Option<Distribution>,
+    pub tip: Option<Distribution>,
+    // gas_consumed of each Transaction as a percentage of its gas_limit.
+    pub gas_utilization_percent: Option<Distribution>,
+    pub total_gas_consumed: u64,
+    pub total_gas_limit: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct FeeSuggestion {
+    pub gas_price: u64,
+    pub tip: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeSuggestions {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub sampled_transactions: u64,
+    pub mempool_size: Option<u64>,
+    pub mean_mempool_size: Option<f64>,
+    // Factor (at least 1) by which tips are raised because the mempool is larger than usual.
+    pub mempool_pressure: f64,
+    pub slow: FeeSuggestion,
+    pub standard: FeeSuggestion,
+    pub fast: FeeSuggestion,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesPoint {
+    pub window_start: u64,
+    pub window_end: u64,
+    pub value: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesReport {
+    pub series: String,
+    pub window_size: u64,
+    pub step_size: u64,
+    pub points: Vec<SeriesPoint>,
+    pub summary: Option<Distribution>,
+}
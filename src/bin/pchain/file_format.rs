@@ -0,0 +1,125 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+// File format module reads input files written in JSON, YAML or TOML into the same json value, so
+// that they are mapped to the same structs. The format is detected from the file extension:
+// `.yaml` or `.yml` for YAML, `.toml` for TOML, and JSON otherwise. YAML and TOML allow comments.
+// Standard input has no extension, so its format is detected from its content.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::input;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl FileFormat {
+    pub fn from_path(path: &str) -> FileFormat {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_lowercase()).as_deref() {
+            Some("yaml") | Some("yml") => FileFormat::Yaml,
+            Some("toml") => FileFormat::Toml,
+            _ => FileFormat::Json,
+        }
+    }
+
+    // Detect returns the format of the file at `path` with `content`. Content read from standard input is
+    // JSON if it is an object, otherwise TOML if it parses as TOML, and YAML otherwise.
+    pub fn detect(path: &str, content: &str) -> FileFormat {
+        if !input::is_stdin(path) {
+            return FileFormat::from_path(path);
+        }
+        if content.trim_start().starts_with('{') {
+            FileFormat::Json
+        } else if FileFormat::Toml.parse(content).is_ok() {
+            FileFormat::Toml
+        } else {
+            FileFormat::Yaml
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Json => "json",
+            FileFormat::Yaml => "yaml",
+            FileFormat::Toml => "toml",
+        }
+    }
+
+    // Parse reads `content` written in this format.
+    pub fn parse(&self, content: &str) -> Result<Value, String> {
+        let parsed = match self {
+            FileFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            FileFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            FileFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| format!("Fail to parse file from {}: {}", self.name(), e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FileFormat;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(FileFormat::from_path("tx.json"), FileFormat::Json);
+        assert_eq!(FileFormat::from_path("./runbooks/tx.YAML"), FileFormat::Yaml);
+        assert_eq!(FileFormat::from_path("tx.yml"), FileFormat::Yaml);
+        assert_eq!(FileFormat::from_path("tx.toml"), FileFormat::Toml);
+        assert_eq!(FileFormat::from_path("tx"), FileFormat::Json);
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(FileFormat::detect("tx.toml", "{}"), FileFormat::Toml);
+        assert_eq!(FileFormat::detect("-", " {\"value\": 1}"), FileFormat::Json);
+        assert_eq!(FileFormat::detect("-", "value = 1\n[[arguments]]\ntype = \"u8\""), FileFormat::Toml);
+        assert_eq!(FileFormat::detect("-", "# comment\nvalue: 1"), FileFormat::Yaml);
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = serde_json::json!({
+            "method_name": "transfer",
+            "arguments": [{ "type": "u64", "value": "100" }],
+        });
+        let yaml = "
+# Transfer to the treasury
+method_name: transfer
+arguments:
+  - type: u64
+    value: \"100\"
+";
+        let toml = r#"
+# Transfer to the treasury
+method_name = "transfer"
+
+[[arguments]]
+type = "u64"
+value = "100"
+"#;
+        assert_eq!(FileFormat::Json.parse(&expected.to_string()), Ok(expected.clone()));
+        assert_eq!(FileFormat::Yaml.parse(yaml), Ok(expected.clone()));
+        assert_eq!(FileFormat::Toml.parse(toml), Ok(expected));
+        assert!(FileFormat::Toml.parse("method_name: transfer").unwrap_err().contains("toml"));
+    }
+}
@@ -0,0 +1,75 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+// Input module reads the files given in arguments. A path of `-` reads standard input instead, so
+// that commands can be composed in pipelines, e.g. `pchain parse calldata --json-file call.json --raw | pchain submit tx --data - ...`.
+// Standard input can only be read once, so at most one argument of a command can be `-`, which main checks before dispatching.
+
+use std::{io::Read, path::Path};
+
+pub const STDIN: &str = "-";
+
+pub fn is_stdin(path: &str) -> bool {
+    path == STDIN
+}
+
+// Exists is true if `path` is standard input or a file.
+pub fn exists(path: &str) -> bool {
+    is_stdin(path) || Path::new(path).is_file()
+}
+
+// Read returns the content of the file at `path`, or all of standard input if `path` is `-`.
+pub fn read(path: &str) -> std::io::Result<Vec<u8>> {
+    if !is_stdin(path) {
+        return std::fs::read(path);
+    }
+    let mut content = vec![];
+    std::io::stdin().read_to_end(&mut content)?;
+    Ok(content)
+}
+
+// Check single stdin returns an error if more than one of the command line `args` is `-`, given either
+// as a separate value or as `--name=-`.
+pub fn check_single_stdin<I: IntoIterator<Item = String>>(args: I) -> Result<(), String> {
+    let count = args.into_iter()
+        .filter(|arg| is_stdin(arg) || (arg.starts_with("--") && arg.ends_with(&format!("={}", STDIN))))
+        .count();
+    if count > 1 {
+        return Err(format!("Standard input can only be read once, but {} arguments are `{}`.", count, STDIN));
+    }
+    Ok(())
+}
+
+// Read to string returns the content of the file at `path`, or all of standard input if `path` is `-`, as utf8.
+pub fn read_to_string(path: &str) -> std::io::Result<String> {
+    String::from_utf8(read(path)?).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod test {
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_check_single_stdin() {
+        assert!(super::check_single_stdin(args(&["pchain", "parse", "prt", "--file", "-", "--type", "tx"])).is_ok());
+        assert!(super::check_single_stdin(args(&["pchain", "submit", "tx", "--data=-"])).is_ok());
+        assert!(super::check_single_stdin(args(&["pchain", "submit", "tx", "--data", "-", "--keypair", "-"])).is_err());
+        assert!(super::check_single_stdin(args(&["pchain", "submit", "tx", "--data=-", "--keypair", "-"])).is_err());
+        assert!(super::check_single_stdin(args(&["pchain", "submit", "tx", "--value", "-1", "--data", "-"])).is_ok());
+    }
+}
@@ -18,9 +18,18 @@ pub mod operations;
 pub mod display_types;
 pub mod args_parser;
 pub mod prt_parser;
+pub mod contract_validator;
+pub mod stats;
+pub mod time_parser;
+pub mod tx_template;
+pub mod file_format;
+pub mod input;
 
-use clap::{Parser, Subcommand};
-use operations::{submit, query, setup::{self, *}, QueryOption, SubmitTxJson};
+use std::io::Write;
+
+use clap::{Args, Parser, Subcommand};
+use display_types::BytesEncoding;
+use operations::{submit, query, query_and_save, setup::{self, *}, fetch::BlockMode, follow::follow_blocks, range::{query_block_range, query_tx_range, PageRange}, export::{export, ExportFormat, ExportKind, ExportRange}, verify::verify_chain, analyze::{analyze_proposers, analyze_throughput, analyze_fees}, fees::{analyze_suggest_fees, resolve_fees, FeeLevel}, series::{analyze_series, AnalyticsSeries, SeriesOutput}, batch::submit_batch, events::{query_events, EventFilter}, history::{account_history, Direction}, tx_status::query_tx, QueryOption, SubmitTxJson};
 
 
 type Base64Address = String;
@@ -74,7 +83,21 @@ enum VeryLightCLI {
     Parse {
         #[clap(subcommand)]
         parse_subcommand: Parse,
-    }
+    },
+
+    /// Export Blocks, Transactions or Receipts in a range to files for offline analysis.
+    #[clap(display_order=7)]
+    Export {
+        #[clap(subcommand)]
+        export_subcommand: Export,
+    },
+
+    /// Verify data returned by the fullnode locally, e.g. the integrity of a range of Blocks.
+    #[clap(display_order=8)]
+    Verify {
+        #[clap(subcommand)]
+        verify_subcommand: Verify,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -95,19 +118,20 @@ enum Submit {
         value: u64,
 
         /// XPLL/TXPLL to tip to the proposing Validator. Set this to a high value if you need Transaction to be included in a block quickly (in Grays).
-        #[clap(long="tip", display_order=4)]
-        tip: u64,
+        #[clap(long="tip", required_unless_present="fee-level", display_order=4)]
+        tip: Option<u64>,
 
         /// Maximum number of Gas units that you are willing to consume on executing this Transaction. If this is set to low, your Transaction may not execute to completion.
         #[clap(long="gas-limit", display_order=5)]
         gas_limit: u64,
 
         /// XPLL/TXPLL you are willing to pay per unit Gas consumed in the execution of your transaction (in Grays). This needs to be greater than your Account balance for your transaction to be included in a block. 
-        #[clap(long="gas-price", display_order=6)]
-        gas_price: u64,
+        #[clap(long="gas-price", required_unless_present="fee-level", display_order=6)]
+        gas_price: Option<u64>,
 
         /// Free field. Can be either 1. 'null', or 2., a Base64 encoded message that will be included in the Blockchain and passed into the Smart Contract
-        /// (if 'to-address' identifies a Contract Account), or 3. A relative path (starting with '.') to a Compiled Smart Contract '.wasm' file, if this is a Deploy Transaction.
+        /// (if 'to-address' identifies a Contract Account), or 3. A relative path (starting with '.') to a Compiled Smart Contract '.wasm' file, if this is a Deploy Transaction,
+        /// or 4. `-` to read the raw bytes of the message or contract from standard input.
         #[clap(long="data", display_order=7)]
         data: String,
 
@@ -115,39 +139,219 @@ enum Submit {
         #[clap(long="deploy-args", display_order=8)]
         deploy_args: Option<String>,
 
+        /// [Optional] Relative path to a json, yaml or toml file containing typed arguments for the "init" entrypoint of the deployed Contract, 
+        /// in the same format as the `arguments` array used by `parse calldata`. Cannot be used together with 'deploy-args'.
+        #[clap(long="deploy-args-file", conflicts_with="deploy-args", display_order=9)]
+        deploy_args_file: Option<String>,
+
         /// Number of Transactions included on-Chain from 'from_address', or 'nonce', for short. You can simply use the nonce you get by Query-ing on from_address directly.
-        #[clap(long="nonce", display_order=9)]
+        #[clap(long="nonce", display_order=10)]
         nonce: u64,
 
         /// Relative path to a JSON file containing your secret key, public key, and keypair. Read the VeryLight repository README.md for the file format, or generate a 'keypair.json' using the 'Setup' command.
         /// This is used to produce a cryptographic signature that proves that 'you' are authorized to make this Transaction.
-        #[clap(long="path-to-keypair-json", display_order=10)]
+        #[clap(long="path-to-keypair-json", display_order=11)]
         keypair: String,
+
+        /// slow, standard or fast. Use the gas price and tip suggested by 'analyze suggest-fees' for this level. Explicit 'tip' and 'gas-price' take precedence.
+        #[clap(long="fee-level", possible_values=&["slow", "standard", "fast"], display_order=12)]
+        fee_level: Option<FeeLevel>,
     },
 
     /// Submit tx from json file
     #[clap(arg_required_else_help = true, display_order=2)]
     TxFrom {
 
-        /// Relative path to a JSON file of Transaction. Example json file:
+        /// Relative path to a JSON, YAML (.yaml, .yml) or TOML (.toml) file of Transaction, or `-` to read standard input. Example json file:
         /// {
         ///   "from_address": "1a99UDMoXm88AdzeGSmeOQOX0NHpMRcnTW1IcE7Nwl4=",
         ///   "to_address": "MJrfQCg_7Gb7Spw6v8zalYIETwwks8aoI7HrGofMRHY",
         ///   "value": 1,
         ///   "tip": 0,
-        ////   "gas_limit": 67500000,
+        ///   "gas_limit": 67500000,
         ///   "gas_price": 1,
         ///   "data": "",
         ///   "deploy_args": "",
         ///   "nonce": 138,
         ///   "path_to_keypair_json": "../keypair.json"
         /// }
+        /// The file may contain placeholders: ${NAME} or ${NAME:-default} for a variable, ${key:<name>.<field>} for a field of the
+        /// address book at '~/.parallelchain/pchain_cli/address_book.json', and ${nonce:auto} for the on-chain nonce of "from_address".
         #[clap(long="file", display_order=1)]
         file: String,
+
+        /// [Optional] Value of a template variable in the form `NAME=value`. Repeat for multiple variables. Variables not given are read from the environment.
+        #[clap(long="var", display_order=2)]
+        var: Vec<String>,
+    },
+
+    /// Call a method of a Contract, building the calldata from typed arguments.
+    #[clap(arg_required_else_help = true, display_order=3)]
+    Call {
+        /// 'Sending address' of this Transaction. Base64 encoded Ed25519 Public Key (32 bytes).
+        #[clap(long="from-address", display_order=1)]
+        from_address : Base64Address,
+
+        /// Address of the Contract Account to call. Base64 encoded (32 bytes).
+        #[clap(long="to", display_order=2)]
+        to_address : Base64Address,
+
+        /// Name of the Contract method to call.
+        #[clap(long="method", display_order=3)]
+        method: String,
+
+        /// [Optional] Relative path to a json, yaml or toml file containing the method arguments, in the same format as the `arguments` array used by `parse calldata`.
+        #[clap(long="args-file", display_order=4)]
+        args_file: Option<String>,
+
+        /// [Optional] A method argument in the form `type:value`, e.g. `u64:100`. Repeat for multiple arguments. 
+        /// These are appended after the arguments from 'args-file'. Accept the data types listed in `parse calldata`.
+        #[clap(long="arg", display_order=5)]
+        arg: Vec<String>,
+
+        /// XPLL/TXPLL to transfer to the Contract Account (in Grays).
+        #[clap(long="value", default_value="0", display_order=6)]
+        value: u64,
+
+        /// XPLL/TXPLL to tip to the proposing Validator (in Grays).
+        #[clap(long="tip", required_unless_present="fee-level", display_order=7)]
+        tip: Option<u64>,
+
+        /// Maximum number of Gas units that you are willing to consume on executing this Transaction.
+        #[clap(long="gas-limit", display_order=8)]
+        gas_limit: u64,
+
+        /// XPLL/TXPLL you are willing to pay per unit Gas consumed in the execution of your transaction (in Grays).
+        #[clap(long="gas-price", required_unless_present="fee-level", display_order=9)]
+        gas_price: Option<u64>,
+
+        /// Number of Transactions included on-Chain from 'from_address'.
+        #[clap(long="nonce", display_order=10)]
+        nonce: u64,
+
+        /// Relative path to a JSON file containing your secret key, public key, and keypair.
+        #[clap(long="path-to-keypair-json", display_order=11)]
+        keypair: String,
+
+        /// slow, standard or fast. Use the gas price and tip suggested by 'analyze suggest-fees' for this level. Explicit 'tip' and 'gas-price' take precedence.
+        #[clap(long="fee-level", possible_values=&["slow", "standard", "fast"], display_order=12)]
+        fee_level: Option<FeeLevel>,
+    },
+
+    /// Submit many Transactions from a JSON lines file, with sequential nonces. Running it again resumes a partially completed batch.
+    #[clap(arg_required_else_help = true, display_order=4)]
+    Batch {
+        /// Relative path to a JSON lines file with one Transaction per line, in the format of 'tx-from' without "nonce".
+        /// Nonces are assigned in file order from the on-chain nonce of each "from_address".
+        #[clap(long="file", display_order=1)]
+        file: String,
+
+        /// [Optional] Path of the JSON lines file that the nonce, hash and outcome of each Transaction is appended to. Defaults to '<file>.results.jsonl'.
+        /// Lines recorded as submitted in it are skipped, and the others are re-sent with the nonce recorded for them, unless that nonce has been used on chain since.
+        #[clap(long="results", display_order=2)]
+        results: Option<String>,
+
+        /// Maximum number of senders whose Transactions are submitted at the same time. The Transactions of one sender are always submitted one at a time, in nonce order.
+        #[clap(long="concurrency", default_value="4", display_order=3)]
+        concurrency: usize,
+    },
+
+    /// Replace a Transaction stuck in the mempool with the same Transaction at the same nonce and a higher tip.
+    /// The stuck Transaction is given either by the file it was submitted from, or by its sender, nonce and fields.
+    #[clap(arg_required_else_help = true, display_order=5)]
+    Replace {
+        /// Nonce of the stuck Transaction.
+        #[clap(long="nonce", display_order=1)]
+        nonce: u64,
+
+        /// New tip (in Grays). Checked to be higher than the "tip" in 'file', or 'replaced-tip' without 'file'.
+        #[clap(long="tip", display_order=2)]
+        tip: u64,
+
+        /// [Optional] New gas price (in Grays). Defaults to the "gas_price" in 'file'. Required without 'file'.
+        #[clap(long="gas-price", required_unless_present="file", display_order=3)]
+        gas_price: Option<u64>,
+
+        /// [Optional] Relative path to the JSON file of the stuck Transaction, in the format of 'tx-from', including placeholders. Its "nonce" is replaced by 'nonce'.
+        /// Without it, the fields of the stuck Transaction are given by the arguments below.
+        #[clap(long="file", display_order=4)]
+        file: Option<String>,
+
+        /// [Optional] Value of a template variable of 'file' in the form `NAME=value`. Repeat for multiple variables.
+        #[clap(long="var", requires="file", display_order=5)]
+        var: Vec<String>,
+
+        /// 'Sending address' of the stuck Transaction. Base64 encoded Ed25519 Public Key (32 bytes).
+        #[clap(long="from-address", required_unless_present="file", conflicts_with="file", display_order=6)]
+        from_address: Option<Base64Address>,
+
+        /// 'Receiver address' of the stuck Transaction, or 'null' for a Deploy Transaction.
+        #[clap(long="to-address", required_unless_present="file", conflicts_with="file", display_order=7)]
+        to_address: Option<Base64Address>,
+
+        /// XPLL/TXPLL transferred by the stuck Transaction (in Grays).
+        #[clap(long="value", required_unless_present="file", conflicts_with="file", display_order=8)]
+        value: Option<u64>,
+
+        /// Tip of the stuck Transaction (in Grays).
+        #[clap(long="replaced-tip", required_unless_present="file", conflicts_with="file", display_order=9)]
+        replaced_tip: Option<u64>,
+
+        /// Gas limit of the stuck Transaction.
+        #[clap(long="gas-limit", required_unless_present="file", conflicts_with="file", display_order=10)]
+        gas_limit: Option<u64>,
+
+        /// [Optional] Data of the stuck Transaction, as in 'tx'. Defaults to 'null'.
+        #[clap(long="data", default_value="null", conflicts_with="file", display_order=11)]
+        data: String,
+
+        /// Relative path to a JSON file containing your secret key, public key, and keypair.
+        #[clap(long="path-to-keypair-json", required_unless_present="file", conflicts_with="file", display_order=12)]
+        keypair: Option<String>,
+    },
+
+    /// Cancel a Transaction stuck in the mempool by sending a zero-value Transaction to yourself at the same nonce.
+    #[clap(arg_required_else_help = true, display_order=6)]
+    Cancel {
+        /// 'Sending address' of the stuck Transaction. Base64 encoded Ed25519 Public Key (32 bytes).
+        #[clap(long="from-address", display_order=1)]
+        from_address : Base64Address,
+
+        /// Nonce of the stuck Transaction.
+        #[clap(long="nonce", display_order=2)]
+        nonce: u64,
+
+        /// XPLL/TXPLL to tip to the proposing Validator (in Grays). Set this higher than the tip of the stuck Transaction.
+        #[clap(long="tip", required_unless_present="fee-level", display_order=3)]
+        tip: Option<u64>,
+
+        /// Maximum number of Gas units that you are willing to consume on executing this Transaction.
+        #[clap(long="gas-limit", display_order=4)]
+        gas_limit: u64,
+
+        /// XPLL/TXPLL you are willing to pay per unit Gas consumed in the execution of your transaction (in Grays).
+        #[clap(long="gas-price", required_unless_present="fee-level", display_order=5)]
+        gas_price: Option<u64>,
+
+        /// Relative path to a JSON file containing your secret key, public key, and keypair.
+        #[clap(long="path-to-keypair-json", display_order=6)]
+        keypair: String,
+
+        /// slow, standard or fast. Use the gas price and tip suggested by 'analyze suggest-fees' for this level. Explicit 'tip' and 'gas-price' take precedence.
+        #[clap(long="fee-level", possible_values=&["slow", "standard", "fast"], display_order=7)]
+        fee_level: Option<FeeLevel>,
     }
 }
 
 
+// Shared by the commands which display Events and Receipts.
+#[derive(Debug, Args)]
+struct EventEncodingArgs {
+    /// Encoding of Event topics and values, and Receipt return values: utf8, hex, base64, or auto (utf8 if valid, otherwise base64).
+    #[clap(long="event-encoding", default_value="auto", possible_values=&["utf8", "hex", "base64", "auto"])]
+    event_encoding: BytesEncoding,
+}
+
 #[derive(Debug, Subcommand)]
 enum Query {
     /// Query information related to Accounts.
@@ -176,9 +380,9 @@ enum Query {
         #[clap(long="latest", display_order=4)]
         latest : bool,
 
-	    /// Size of query window.
-        #[clap(long="size", display_order=5)]
-	    size: u64,
+	    /// Size of query window. Not required with 'follow' or 'from'.
+        #[clap(long="size", required_unless_present_any=&["follow", "from"], display_order=5)]
+	    size: Option<u64>,
 
         /// "true" or "false". Specifying the former causes this endpoint to return only BlockHeaders. Specifying the latter causes the endpoint to also return Blocks' Transactions (sans Receipts and Events)
         #[clap(long="header-only", display_order=6)]
@@ -186,7 +390,42 @@ enum Query {
 
         /// "true", "false". Default to be "false". Specifying the former causes this endpoint to return a summary of header only. Covering height, block_hash, state_hash, receipts_hash, time, tx_count( if header_only is false)
         #[clap(long="summary-only", display_order=7)]
-        summary_only: Option<String>
+        summary_only: Option<String>,
+
+        /// Specify this flag to keep printing new blocks as they are produced, starting from the latest block. Missed blocks are backfilled. Stop with Ctrl-C.
+        #[clap(long="follow", display_order=8)]
+        follow: bool,
+
+        /// Polling interval (in seconds) of 'follow'.
+        #[clap(long="interval", default_value="2", display_order=9)]
+        interval: u64,
+
+        /// Specify this flag to print one JSON object per line in 'follow' or 'from'.
+        #[clap(long="json", display_order=10)]
+        json: bool,
+
+        #[clap(flatten)]
+        encoding: EventEncodingArgs,
+
+        /// Block number to start a range query from. Blocks are fetched page by page and printed as they arrive.
+        #[clap(long="from", conflicts_with_all=&["block-num", "block-hash", "tx-hash", "latest", "follow"], display_order=12)]
+        from: Option<u64>,
+
+        /// Last Block number (inclusive) of a range query. Default to be the latest Block.
+        #[clap(long="to", requires="from", display_order=13)]
+        to: Option<u64>,
+
+        /// Number of Blocks requested at once in a range query (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=14)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently in a range query.
+        #[clap(long="concurrency", default_value="4", display_order=15)]
+        concurrency: usize,
+
+        /// Path to save each Block (or BlockHeader if 'header-only') as a Prt file, named `<path>.<block number>.blk.prt` (or `.bh.prt`), which can be displayed with `parse prt`. Use `-` to write the Prt to standard output, if only one Block is returned. Existing files are not overwritten. Not allowed with 'summary-only'.
+        #[clap(long="save", conflicts_with_all=&["follow", "from"], display_order=16)]
+        save: Option<String>,
     },
 
     /// Query VeryLight's network configuration 
@@ -215,6 +454,14 @@ enum Query {
 	    /// Key of world state. BASE64 encoded of key defined in contract
         #[clap(long="key", display_order=2)]
 	    key: Base64String,
+
+        /// Specify this flag to query the State Proofs of the key instead of its value.
+        #[clap(long="prove", display_order=3)]
+        prove: bool,
+
+        /// Path to save the State Proofs as a Prt file, named `<path>.sprfs.prt`, which can be displayed with `parse prt`. Use `-` to write it to standard output. An existing file is not overwritten.
+        #[clap(long="save", requires="prove", display_order=4)]
+        save: Option<String>,
     },
 
     /// Query multiple Transactions.
@@ -232,13 +479,40 @@ enum Query {
         #[clap(long="latest", display_order=3)]
         latest : bool,
 
-        /// Size of query window.
-        #[clap(long="size", display_order=4)]
-	    size: u64,
+        /// Size of query window. Not required with 'from'.
+        #[clap(long="size", required_unless_present="from", display_order=4)]
+	    size: Option<u64>,
 
         /// "true", "false". Default to be "false". Specifying the former causes this endpoint to return a summary of header only. Covering height, block_hash, state_hash, receipts_hash, time, tx_count( if header_only is false)
         #[clap(long="summary-only", display_order=5)]
-        summary_only: Option<String>
+        summary_only: Option<String>,
+
+        #[clap(flatten)]
+        encoding: EventEncodingArgs,
+
+        /// Transaction number to start a range query from. Transactions are fetched page by page and printed as they arrive.
+        #[clap(long="from", conflicts_with_all=&["tx-num", "tx-hash", "latest"], display_order=7)]
+        from: Option<u64>,
+
+        /// Last Transaction number (inclusive) of a range query. Default to be the latest Transaction.
+        #[clap(long="to", requires="from", display_order=8)]
+        to: Option<u64>,
+
+        /// Number of Transactions requested at once in a range query (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=9)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently in a range query.
+        #[clap(long="concurrency", default_value="4", display_order=10)]
+        concurrency: usize,
+
+        /// Specify this flag to print one JSON object per line in 'from'.
+        #[clap(long="json", requires="from", display_order=11)]
+        json: bool,
+
+        /// Path to save each Transaction and its Receipt as Prt files, named `<path>.<tx number>.tx.prt` and `<path>.<tx number>.recp.prt`, which can be displayed with `parse prt`. Existing files are not overwritten. Not allowed with 'summary-only'.
+        #[clap(long="save", conflicts_with="from", display_order=12)]
+        save: Option<String>,
     },
 
     /// Query Transaction Proof
@@ -250,6 +524,9 @@ enum Query {
 	    /// Identifies the target Transaction
         #[clap(long="tx-hash", display_order=2)]
         tx_hash: Base64Hash,
+        /// Path to save the proof as a Prt file, named `<path>.mprf.prt`, which can be displayed with `parse prt`. Use `-` to write it to standard output. An existing file is not overwritten.
+        #[clap(long="save", display_order=3)]
+        save: Option<String>,
     },
     /// Query Receipt Proof
     #[clap(arg_required_else_help = true, display_order=7)]
@@ -260,11 +537,67 @@ enum Query {
 	    /// Identifies the target Transaction
         #[clap(long="tx-hash", display_order=2)]
         tx_hash: Base64Hash,
+        /// Path to save the proof as a Prt file, named `<path>.mprf.prt`, which can be displayed with `parse prt`. Use `-` to write it to standard output. An existing file is not overwritten.
+        #[clap(long="save", display_order=3)]
+        save: Option<String>,
     },
 
     /// Query size of mempool
     #[clap(arg_required_else_help = false, display_order=8)]
-    Mempoolsize
+    Mempoolsize,
+
+    /// Query a single Transaction by hash, with its Receipt and containing Block, or report whether it is still pending or unknown. Exits with an error unless it is included or pending.
+    #[clap(arg_required_else_help = true, display_order=10)]
+    Tx {
+        /// Hash of the Transaction you'd like to query.
+        #[clap(long="hash", display_order=1)]
+        hash: Base64Hash,
+
+        /// [Optional] Sending address of the Transaction. Together with 'nonce', tells whether a Transaction which is not included is still pending.
+        #[clap(long="from-address", requires="nonce", display_order=2)]
+        from_address: Option<Base64Address>,
+
+        /// [Optional] Nonce of the Transaction. Together with 'from-address', tells whether a Transaction which is not included is still pending.
+        #[clap(long="nonce", requires="from-address", display_order=3)]
+        nonce: Option<u64>,
+
+        /// Specify this flag to print the result as JSON.
+        #[clap(long="json", display_order=4)]
+        json: bool,
+
+        #[clap(flatten)]
+        encoding: EventEncodingArgs,
+    },
+
+    /// Search the Events emitted by Transactions in a range of Blocks.
+    #[clap(arg_required_else_help = true, display_order=9)]
+    Events {
+        /// Block number of the first Block to search.
+        #[clap(long="from-block", display_order=1)]
+        from_block: u64,
+
+        /// [Optional] Block number of the last Block to search. Defaults to the latest Block.
+        #[clap(long="to-block", display_order=2)]
+        to_block: Option<u64>,
+
+        /// [Optional] Only return Events whose topic starts with this (utf8) prefix.
+        #[clap(long="topic", display_order=3)]
+        topic: Option<String>,
+
+        /// [Optional] Only return Events emitted by Transactions to this Contract address.
+        #[clap(long="contract", display_order=4)]
+        contract: Option<Base64Address>,
+
+        /// [Optional] Decode Event values as this data type. Accept the data types listed in `parse callback`.
+        #[clap(long="value-type", display_order=5)]
+        value_type: Option<String>,
+
+        /// Specify this flag to print one JSON object per line.
+        #[clap(long="json", display_order=6)]
+        json: bool,
+        #[clap(flatten)]
+        encoding: EventEncodingArgs,
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -308,7 +641,134 @@ enum Crypto {
         /// A message to sign, encoded in Base64.
         #[clap(long="message", display_order=1)]
         message: String,
-    } 
+    },
+
+    /// Compute the address a Contract will be deployed to, before sending the Deploy Transaction.
+    #[clap(arg_required_else_help = true, display_order=3)]
+    ContractAddress {
+        /// Relative path (starting with '.') to a Compiled Smart Contract '.wasm' file, or the Base64 encoded contract code.
+        #[clap(long="code", display_order=1)]
+        code: String,
+
+        /// Address of the External Account sending the Deploy Transaction. Base64 encoded (32 bytes).
+        #[clap(long="from", display_order=2)]
+        from_address: Base64Address,
+
+        /// [Optional] Nonce of the Deploy Transaction. Defaults to the current on-chain nonce of 'from'.
+        #[clap(long="nonce", display_order=3)]
+        nonce: Option<u64>,
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Export {
+    /// Export Blocks by Block number.
+    #[clap(arg_required_else_help = true, display_order=1)]
+    Blocks {
+        /// First Block number to export.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Block number (inclusive) to export. Default to be the latest Block.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Output format: jsonl (one JSON object per line), csv, or prt (one `<block number>.blk.prt` file per item, readable by 'parse prt').
+        #[clap(long="format", default_value="jsonl", possible_values=&["jsonl", "csv", "prt"], display_order=3)]
+        format: ExportFormat,
+
+        /// Output directory. Created if it does not exist. Running the same command again resumes an interrupted export.
+        #[clap(long="out", display_order=4)]
+        out: String,
+
+        /// Number of items requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=5)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=6)]
+        concurrency: usize,
+    },
+
+    /// Export Transactions (with their Receipts in jsonl) by Transaction number.
+    #[clap(arg_required_else_help = true, display_order=2)]
+    Txs {
+        /// First Transaction number to export.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Transaction number (inclusive) to export. Default to be the latest Transaction.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Output format: jsonl (one JSON object per line), csv, or prt (one `<tx number>.tx.prt` file per item, readable by 'parse prt').
+        #[clap(long="format", default_value="jsonl", possible_values=&["jsonl", "csv", "prt"], display_order=3)]
+        format: ExportFormat,
+
+        /// Output directory. Created if it does not exist. Running the same command again resumes an interrupted export.
+        #[clap(long="out", display_order=4)]
+        out: String,
+
+        /// Number of items requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=5)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=6)]
+        concurrency: usize,
+    },
+
+    /// Export Receipts by Transaction number.
+    #[clap(arg_required_else_help = true, display_order=3)]
+    Receipts {
+        /// First Transaction number to export.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Transaction number (inclusive) to export. Default to be the latest Transaction.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Output format: jsonl (one JSON object per line), csv, or prt (one `<tx number>.recp.prt` file per item, readable by 'parse prt').
+        #[clap(long="format", default_value="jsonl", possible_values=&["jsonl", "csv", "prt"], display_order=3)]
+        format: ExportFormat,
+
+        /// Output directory. Created if it does not exist. Running the same command again resumes an interrupted export.
+        #[clap(long="out", display_order=4)]
+        out: String,
+
+        /// Number of items requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=5)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=6)]
+        concurrency: usize,
+    },
+
+}
+
+#[derive(Debug, Subcommand)]
+enum Verify {
+    /// Check the Blocks in a range: hash linkage to the previous Block, consecutive block numbers, and the hash and signature of every Transaction.
+    #[clap(arg_required_else_help = true, display_order=1)]
+    Chain {
+        /// First Block number to verify. If it is not the genesis Block, it is also checked against its parent.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Block number (inclusive) to verify. Default to be the latest Block.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Number of Blocks requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=3)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=4)]
+        concurrency: usize,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -318,16 +778,20 @@ enum Parse {
     #[clap(arg_required_else_help = true, display_order=1)]
     Calldata {
         
-        /// Relative Path to json file
+        /// Relative Path to json file, or to a YAML (.yaml, .yml) or TOML (.toml) file with the same fields.
         /// Accept data format: i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, bool, String, [32], [64],
         /// Vec<i8>, Vec<i16>, Vec<i32>, Vec<i64>, Vec<i128>, Vec<u8>, Vec<u16>, Vec<u32>, Vec<u64>, Vec<u128>, 
         /// Vec<bool>, Vec<String>, address.
         /// Example values in Vec or slice: [0,1,2].
         /// The data type [32] and [64] refers to slice of 32 bytes and slice of 64 bytes. 
         /// `address` must be base64url encoded string
+        /// Use `-` to read standard input.
         #[clap(long="json-file", display_order=1)]
-        json_file :String
+        json_file :String,
 
+        /// Specify this flag to write only the raw bytes of `data` to standard output, e.g. to pipe them into `submit tx --data -`.
+        #[clap(long="raw", display_order=2)]
+        raw: bool,
     },
 
     /// Parse return value from result of contract call.
@@ -350,8 +814,36 @@ enum Parse {
     /// Parse protocol types file to display the data in the structure
     #[clap(arg_required_else_help = true, display_order=3)]
     Prt {
+        /// Path to a `<name>.<data type extension>.prt` file, or `-` to read standard input together with 'type'.
         #[clap(long="file", display_order=1)]
-        file: String
+        file: String,
+
+        /// [Optional] Data type extension of the file content: bh, blk, tx, recp, evt, call, mprf or sprfs. Required when 'file' is `-`, and overrides the extension of 'file' otherwise.
+        #[clap(long="type", possible_values=&["bh", "blk", "tx", "recp", "evt", "call", "mprf", "sprfs"], display_order=3)]
+        data_type: Option<String>,
+
+        #[clap(flatten)]
+        encoding: EventEncodingArgs,
+
+        /// Specify this flag to print the data as JSON, which can be encoded back into a Prt file with `parse prt-encode`. Event topics and values, and Receipt return values are always Base64URL encoded in JSON, whatever 'event-encoding'.
+        #[clap(long="json", display_order=4)]
+        json: bool,
+    },
+
+    /// Encode data displayed as JSON by `parse prt --json` into a protocol types file. The inverse of `parse prt`.
+    #[clap(arg_required_else_help = true, display_order=4)]
+    PrtEncode {
+        /// Data type extension of the data: bh, blk, tx, recp, evt, call, mprf or sprfs. Default to be the extension of 'out'.
+        #[clap(long="type", possible_values=&["bh", "blk", "tx", "recp", "evt", "call", "mprf", "sprfs"], display_order=1)]
+        data_type: Option<String>,
+
+        /// Relative path to json file, or to a YAML (.yaml, .yml) or TOML (.toml) file with the same fields. Use `-` to read standard input.
+        #[clap(long="json", display_order=2)]
+        json: String,
+
+        /// Path to the Prt file to write, which is given the extension `.<data type extension>.prt` if it does not end with it. Use `-` to write to standard output together with 'type'.
+        #[clap(long="out", display_order=3)]
+        out: String,
     }
 }
 
@@ -360,41 +852,152 @@ enum Analyze {
     /// Gas-Per-Block computed on the basis of a moving-window average.
     #[clap(arg_required_else_help = true, display_order=1)]
     GasPerBlock {
-        /// Start-time of period of interest (Unix timestamp).
-        #[clap(long="start-time", display_order=1)]
-        start_time: u64,
+        /// Start-time of period of interest: a Unix timestamp, an RFC3339 datetime (e.g. 2022-08-09T00:00:00Z), or a duration before now (e.g. 24h).
+        #[clap(long="start-time", visible_alias="since", display_order=1)]
+        start_time: String,
 
-        /// End-time of period of interest (Unix timestamp).
-        #[clap(long="end-time", display_order=2)]
-        end_time: u64,
+        /// End-time of period of interest: a Unix timestamp, an RFC3339 datetime, `now`, or a duration before now.
+        #[clap(long="end-time", visible_alias="until", default_value="now", display_order=2)]
+        end_time: String,
 
-        /// Moving window size (in seconds).
+        /// Moving window size: seconds, or a duration such as 5m or 1h.
         #[clap(long="window-size", display_order=3)]
-        window_size: u64,
+        window_size: String,
 
-        /// Moving window step-size in seconds. E.g., Non overlapping moving windows have step-size == window size.
+        /// Moving window step size: seconds, or a duration such as 5m or 1h. Must not exceed the window size. E.g., Non overlapping moving windows have step size == window size.
         #[clap(long="step-size", display_order=4)]
-        step_size: u64,
+        step_size: String,
+
+        /// [Optional] Path of a CSV file to save the series to, with the start and end time of each window.
+        #[clap(long="csv", display_order=5)]
+        csv: Option<String>,
+
+        /// Specify this flag to also print a line chart of the series.
+        #[clap(long="chart", display_order=6)]
+        chart: bool,
+
+        /// Specify this flag to print the series as JSON.
+        #[clap(long="json", display_order=7)]
+        json: bool,
     },
 
     /// Mempool size (in bytes) computed on the basis of a moving-window average.
     #[clap(arg_required_else_help = true, display_order=2)]
     MempoolSize {
-        /// Start-time of period of interest (Unix timestamp).
-        #[clap(long="start-time", display_order=1)]
-        start_time: u64,
+        /// Start-time of period of interest: a Unix timestamp, an RFC3339 datetime (e.g. 2022-08-09T00:00:00Z), or a duration before now (e.g. 24h).
+        #[clap(long="start-time", visible_alias="since", display_order=1)]
+        start_time: String,
 
-        /// End-time of period of interest (Unix timestamp).
-        #[clap(long="end-time", display_order=2)]
-        end_time: u64,
+        /// End-time of period of interest: a Unix timestamp, an RFC3339 datetime, `now`, or a duration before now.
+        #[clap(long="end-time", visible_alias="until", default_value="now", display_order=2)]
+        end_time: String,
 
-        /// Moving window size (in seconds). 
+        /// Moving window size: seconds, or a duration such as 5m or 1h.
         #[clap(long="window-size", display_order=3)]
-        window_size: u64,
+        window_size: String,
 
-        /// Moving window step size in seconds. E.g., Non overlapping moving window should have step size = window size 
+        /// Moving window step size: seconds, or a duration such as 5m or 1h. Must not exceed the window size. E.g., Non overlapping moving windows have step size == window size.
         #[clap(long="step-size", display_order=4)]
-        step_size: u64
+        step_size: String,
+
+        /// [Optional] Path of a CSV file to save the series to, with the start and end time of each window.
+        #[clap(long="csv", display_order=5)]
+        csv: Option<String>,
+
+        /// Specify this flag to also print a line chart of the series.
+        #[clap(long="chart", display_order=6)]
+        chart: bool,
+
+        /// Specify this flag to print the series as JSON.
+        #[clap(long="json", display_order=7)]
+        json: bool,
+    },
+
+    /// Blocks proposed, share of total and tips collected per proposer, and missed slots, computed from the Blocks in a range.
+    #[clap(arg_required_else_help = true, display_order=3)]
+    Proposers {
+        /// First Block number to analyze.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Block number (inclusive) to analyze. Default to be the latest Block.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Expected time (in seconds) between Blocks. Default to be the median interval between Blocks in the range.
+        #[clap(long="slot-time", display_order=3)]
+        slot_time: Option<u64>,
+
+        /// Specify this flag to print the result as JSON instead of a table.
+        #[clap(long="json", display_order=4)]
+        json: bool,
+
+        /// Number of Blocks requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=5)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=6)]
+        concurrency: usize,
+    },
+    /// Transactions per second, Transactions per Block and intervals between Blocks, computed from the Blocks in a range.
+    #[clap(arg_required_else_help = true, display_order=4)]
+    Throughput {
+        /// First Block number to analyze.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Block number (inclusive) to analyze. Default to be the latest Block.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Specify this flag to print the result as JSON.
+        #[clap(long="json", display_order=3)]
+        json: bool,
+
+        /// Number of Blocks requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=4)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=5)]
+        concurrency: usize,
+    },
+
+    /// Percentiles of gas price and tip, and gas consumed versus gas limit, computed from the Transactions in a range of Blocks.
+    #[clap(arg_required_else_help = true, display_order=5)]
+    Fees {
+        /// First Block number to analyze.
+        #[clap(long="from", display_order=1)]
+        from: u64,
+
+        /// Last Block number (inclusive) to analyze. Default to be the latest Block.
+        #[clap(long="to", display_order=2)]
+        to: Option<u64>,
+
+        /// Specify this flag to print the result as JSON.
+        #[clap(long="json", display_order=3)]
+        json: bool,
+
+        /// Number of Blocks requested at once (at most 100).
+        #[clap(long="page-size", default_value="50", display_order=4)]
+        page_size: u64,
+
+        /// Number of pages requested concurrently.
+        #[clap(long="concurrency", default_value="4", display_order=5)]
+        concurrency: usize,
+    },
+
+    /// Recommend slow, standard and fast gas prices and tips, from the Transactions in recent Blocks and the current mempool size.
+    #[clap(display_order=6)]
+    SuggestFees {
+        /// Number of latest Blocks to sample.
+        #[clap(long="blocks", default_value="50", display_order=1)]
+        blocks: u64,
+
+        /// Specify this flag to print the result as JSON.
+        #[clap(long="json", display_order=2)]
+        json: bool,
     },
 }
 
@@ -448,6 +1051,56 @@ enum Account {
         /// Vec<bool>, Vec<String>. 
         #[clap(long="expected", display_order=3)]
         expected_return_type: Option<String>
+    },
+
+    /// Query contract by calling a view method with typed arguments, and decode the returned value.
+    #[clap(arg_required_else_help = true, display_order=6)]
+    CallView {
+        /// Address of the Contract Account you'd like to query.
+        #[clap(long="address", display_order=1)]
+        address: Base64Address,
+
+        /// Name of the Contract view method to call.
+        #[clap(long="method", display_order=2)]
+        method: String,
+
+        /// [Optional] Relative path to a json, yaml or toml file containing the method arguments, in the same format as the `arguments` array used by `parse calldata`.
+        #[clap(long="args-file", display_order=3)]
+        args_file: Option<String>,
+
+        /// [Optional] A method argument in the form `type:value`, e.g. `u64:100`. Repeat for multiple arguments.
+        /// These are appended after the arguments from 'args-file'.
+        #[clap(long="arg", display_order=4)]
+        arg: Vec<String>,
+
+        /// [Optional] Data type of the value returned by the view method, e.g. `Vec<u64>`. 
+        /// Accept the data types listed in `parse callback`.
+        #[clap(long="returns", display_order=5)]
+        returns: Option<String>
+    },
+
+    /// Query the Transactions sent from or to an Account, oldest first.
+    #[clap(arg_required_else_help = true, display_order=7)]
+    History {
+        /// Address of the External or Contract Account you'd like to query.
+        #[clap(long="address", display_order=1)]
+        address: Base64Address,
+
+        /// Block number to start searching from. Use the block number printed after a page to get the next page.
+        #[clap(long="from-block", default_value="0", display_order=2)]
+        from_block: u64,
+
+        /// Transactions to the Account (in), from the Account (out), or both.
+        #[clap(long="direction", default_value="both", possible_values=&["in", "out", "both"], display_order=3)]
+        direction: Direction,
+
+        /// Number of Transactions after which a page ends. Pages always end at a Block boundary, so a page may hold a few more.
+        #[clap(long="limit", default_value="100", display_order=4)]
+        limit: u64,
+
+        /// Specify this flag to print one JSON object per line.
+        #[clap(long="json", display_order=5)]
+        json: bool,
     }
 }
 
@@ -457,13 +1110,37 @@ async fn main() {
     // This is the argument collector
     let args = VeryLightCLI::parse();
 
+    if let Err(e) = input::check_single_stdin(std::env::args()) {
+        println!("Error: {}", e);
+        std::process::exit(1);
+    }
+
     // This maps the argument collection to the corresponding function
     match args {
         VeryLightCLI::Submit { submit_subcommand } => {
             match submit_subcommand {
-                Submit::Tx { from_address, to_address, value, tip, gas_limit, gas_price, mut data, deploy_args, nonce, keypair } => {
+                Submit::Tx { from_address, to_address, value, tip, gas_limit, gas_price, mut data, deploy_args, deploy_args_file, nonce, keypair, fee_level } => {
                     if data.to_lowercase() == "null" { data = "".to_string() };
-                    let deploy_args = match deploy_args { Some(str) => str, None=> "".to_string() };
+                    if input::is_stdin(&data) {
+                        // Raw bytes from standard input, e.g. the output of `parse calldata --raw` or a '.wasm' file.
+                        data = match input::read(&data) {
+                            Ok(bytes) => protocol_types::Base64URL::encode(bytes).to_string(),
+                            Err(e) => {
+                                println!("Error: Cannot read data from standard input. {}", e);
+                                std::process::exit(1);
+                            }
+                        };
+                    }
+                    let (tip, gas_price) = resolve_fees(tip, gas_price, fee_level).await;
+                    let deploy_args = match (deploy_args, deploy_args_file) {
+                        (_, Some(file)) => {
+                            // Only the Borsh serialized arguments are passed into the "init" entrypoint.
+                            let (_, arguments) = args_parser::make_call_data("init", args_parser::parse_arguments(file));
+                            protocol_types::Base64URL::encode(arguments).to_string()
+                        },
+                        (Some(str), None) => str,
+                        (None, None) => "".to_string()
+                    };
                     let is_deploy = &to_address == "null"; // To address is null if and only if it is a deploy transaction
                     let submit_tx_json = SubmitTxJson { 
                         from_address, 
@@ -479,10 +1156,56 @@ async fn main() {
                     };
                     submit(submit_tx_json, is_deploy).await
                 },
-                Submit::TxFrom { file } => {
-                    let tx_json = SubmitTxJson::load_tx_json_file(file);
+                Submit::TxFrom { file, var } => {
+                    let tx_json = SubmitTxJson::load_tx_json_file(file, &var, None).await;
                     let is_deploy = &tx_json.to_address == "null"; // To address is null if and only if it is a deploy transaction
                     submit(tx_json, is_deploy).await
+                },
+                Submit::Call { from_address, to_address, method, args_file, arg, value, tip, gas_limit, gas_price, nonce, keypair, fee_level } => {
+                    let (tip, gas_price) = resolve_fees(tip, gas_price, fee_level).await;
+                    let mut arguments = match args_file { Some(file) => args_parser::parse_arguments(file), None => vec![] };
+                    arguments.extend(arg.iter().map(|a| args_parser::parse_typed_arg(a)));
+                    let (data, _) = args_parser::make_call_data(&method, arguments);
+                    let submit_tx_json = SubmitTxJson { 
+                        from_address, 
+                        to_address, 
+                        value, 
+                        tip, 
+                        gas_limit, 
+                        gas_price, 
+                        data: protocol_types::Base64URL::encode(data).to_string(), 
+                        deploy_args: "".to_string(), 
+                        nonce, 
+                        path_to_keypair_json: keypair
+                    };
+                    submit(submit_tx_json, false).await
+                },
+                Submit::Batch { file, results, concurrency } => {
+                    submit_batch(file, results, concurrency).await;
+                },
+                Submit::Replace { nonce, tip, gas_price, file, var, from_address, to_address, value, replaced_tip, gas_limit, data, keypair } => {
+                    let tx_json = match file {
+                        // The nonce of the file is replaced by 'nonce', so `${nonce:auto}` is not queried.
+                        Some(file) => SubmitTxJson::load_tx_json_file(file, &var, Some(nonce)).await,
+                        // clap makes sure that every field is given without 'file'.
+                        None => SubmitTxJson {
+                            from_address: from_address.unwrap(),
+                            to_address: to_address.unwrap(),
+                            value: value.unwrap(),
+                            tip: replaced_tip.unwrap(),
+                            gas_limit: gas_limit.unwrap(),
+                            gas_price: gas_price.unwrap(),
+                            data: if data.to_lowercase() == "null" { "".to_string() } else { data },
+                            deploy_args: "".to_string(),
+                            nonce,
+                            path_to_keypair_json: keypair.unwrap(),
+                        },
+                    };
+                    submit::replace(tx_json, nonce, tip, gas_price).await
+                },
+                Submit::Cancel { from_address, nonce, tip, gas_limit, gas_price, keypair, fee_level } => {
+                    let (tip, gas_price) = resolve_fees(tip, gas_price, fee_level).await;
+                    submit::cancel(from_address, nonce, tip, gas_limit, gas_price, keypair).await
                 }
             }
         },
@@ -509,53 +1232,110 @@ async fn main() {
                                 None => "".to_string()
                             };
                             query(QueryOption::View, vec![address, calldata, expected_return_type]).await;
+                        },
+                        Account::CallView { address, method, args_file, arg, returns } => {
+                            let mut arguments = match args_file { Some(file) => args_parser::parse_arguments(file), None => vec![] };
+                            arguments.extend(arg.iter().map(|a| args_parser::parse_typed_arg(a)));
+                            let (calldata, _) = args_parser::make_call_data(&method, arguments);
+                            let calldata = protocol_types::Base64URL::encode(calldata).to_string();
+                            let returns = match returns { Some(s) => s, None => "".to_string() };
+                            query(QueryOption::View, vec![address, calldata, returns]).await;
+                        },
+                        Account::History { address, from_block, direction, limit, json } => {
+                            account_history(address, from_block, direction, limit, json).await;
                         }
                     }
                 }
-                Query::Blocks { block_num, block_hash, tx_hash, latest, size, header_only, summary_only } => {
+                Query::Blocks { block_num, block_hash, tx_hash, latest, size, header_only, summary_only, follow, interval, json, encoding, from, to, page_size, concurrency, save } => {
+                    let encoding = encoding.event_encoding;
                     let summary_only = match summary_only {
                         Some(s) => s,
                         None => "".to_string()
                     };
+                    let mode = if summary_only == "true" {
+                        BlockMode::BlockSummary
+                    } else if header_only == "true" {
+                        BlockMode::BlockHeaders
+                    } else {
+                        BlockMode::Blocks
+                    };
+                    if follow {
+                        follow_blocks(mode, json, interval, encoding).await;
+                        return;
+                    }
+                    if let Some(from) = from {
+                        query_block_range(mode, PageRange { from, to, page_size, concurrency }, json, encoding).await;
+                        return;
+                    }
+                    if save.is_some() && summary_only == "true" {
+                        println!("Error: 'save' cannot be used with 'summary-only', which is not returned in Borsh bytes.");
+                        std::process::exit(1);
+                    }
+                    // size is required unless following or querying a range.
+                    let size = size.unwrap();
                     if latest {
-                        query(QueryOption::BlocksLatest, vec!["true".to_string(), size.to_string(), header_only, summary_only]).await;    
+                        query_and_save(QueryOption::BlocksLatest, vec!["true".to_string(), size.to_string(), header_only, summary_only], save, encoding).await;    
                     } else if let Some(num) = block_num {
-                        query(QueryOption::BlocksByBlockNum, vec![num.to_string(), size.to_string(), header_only, summary_only]).await;
+                        query_and_save(QueryOption::BlocksByBlockNum, vec![num.to_string(), size.to_string(), header_only, summary_only], save, encoding).await;
                     } else if let Some(hash) = block_hash {
-                        query(QueryOption::BlocksByBlockHash, vec![hash, size.to_string(), header_only, summary_only]).await;
+                        query_and_save(QueryOption::BlocksByBlockHash, vec![hash, size.to_string(), header_only, summary_only], save, encoding).await;
                     } else if let Some(hash) =  tx_hash {
-                        query(QueryOption::BlocksByTxHash, vec![hash, size.to_string(), header_only, summary_only]).await;
+                        query_and_save(QueryOption::BlocksByTxHash, vec![hash, size.to_string(), header_only, summary_only], save, encoding).await;
                     }
                 },
                 Query::Networking { target_url, rich_api_url, analytics_api_url } => {
-                    if target_url == true { println!("target_url is {}", setup::read_config(ConfigField::TargetUrl)) }
-                    if rich_api_url == true { println!("rich_api_url is {}", setup::read_config(ConfigField::RichApiUrl)) }
-                    if analytics_api_url == true { println!("analytics_api_url is {}", setup::read_config(ConfigField::AnalyticsApiUrl)) }
+                    if target_url { println!("target_url is {}", setup::read_config(ConfigField::TargetUrl)) }
+                    if rich_api_url { println!("rich_api_url is {}", setup::read_config(ConfigField::RichApiUrl)) }
+                    if analytics_api_url { println!("analytics_api_url is {}", setup::read_config(ConfigField::AnalyticsApiUrl)) }
                 },
-                Query::State { address, key } => {
-                    query(QueryOption::WorldState, vec![address, key]).await;
+                Query::State { address, key, prove, save } => {
+                    if prove {
+                        query_and_save(QueryOption::WorldStateProof, vec![address, key], save, BytesEncoding::Auto).await;
+                    } else {
+                        query(QueryOption::WorldState, vec![address, key]).await;
+                    }
                 },
-                Query::Txs { tx_num, tx_hash, size, latest, summary_only } => {
+                Query::Txs { tx_num, tx_hash, size, latest, summary_only, encoding, from, to, page_size, concurrency, json, save } => {
+                    let encoding = encoding.event_encoding;
                     let summary_only = match summary_only {
                         Some(s) => s,
                         None => "".to_string()
                     };
+                    if let Some(from) = from {
+                        query_tx_range(summary_only == "true", PageRange { from, to, page_size, concurrency }, json, encoding).await;
+                        return;
+                    }
+                    if save.is_some() && summary_only == "true" {
+                        println!("Error: 'save' cannot be used with 'summary-only', which is not returned in Borsh bytes.");
+                        std::process::exit(1);
+                    }
+                    // size is required unless querying a range.
+                    let size = size.unwrap();
                     if latest {
-                        query(QueryOption::TxsLatest, vec!["true".to_string(), size.to_string(), summary_only]).await;
+                        query_and_save(QueryOption::TxsLatest, vec!["true".to_string(), size.to_string(), summary_only], save, encoding).await;
                     } else if let Some(tx_num) = tx_num {
-                        query(QueryOption::TxsByTxNum, vec![tx_num.to_string(), size.to_string(), summary_only]).await;
+                        query_and_save(QueryOption::TxsByTxNum, vec![tx_num.to_string(), size.to_string(), summary_only], save, encoding).await;
                     } else if let Some(tx_hash) = tx_hash {
-                        query(QueryOption::TxsByTxHash, vec![tx_hash.to_string(), size.to_string(), summary_only]).await;
+                        query_and_save(QueryOption::TxsByTxHash, vec![tx_hash.to_string(), size.to_string(), summary_only], save, encoding).await;
                     }
                 }
-                Query::TxProof { block_hash, tx_hash } => {
-                    query(QueryOption::TxProof, vec![block_hash, tx_hash]).await;
+                Query::Tx { hash, from_address, nonce, json, encoding } => {
+                    let encoding = encoding.event_encoding;
+                    query_tx(hash, from_address.zip(nonce), json, encoding).await;
+                },
+                Query::TxProof { block_hash, tx_hash, save } => {
+                    query_and_save(QueryOption::TxProof, vec![block_hash, tx_hash], save, BytesEncoding::Auto).await;
                 },
-                Query::ReceiptProof { block_hash, tx_hash} => {
-                    query(QueryOption::ReceiptProof, vec![block_hash, tx_hash]).await;
+                Query::ReceiptProof { block_hash, tx_hash, save } => {
+                    query_and_save(QueryOption::ReceiptProof, vec![block_hash, tx_hash], save, BytesEncoding::Auto).await;
                 },
                 Query::Mempoolsize => {
                     query(QueryOption::Mempoolsize, vec![]).await;
+                },
+                Query::Events { from_block, to_block, topic, contract, value_type, json, encoding } => {
+                    let encoding = encoding.event_encoding;
+                    let filter = EventFilter { topic_prefix: topic, contract, value_type };
+                    query_events(from_block, to_block, filter, json, encoding).await;
                 }
             }
         },
@@ -564,18 +1344,9 @@ async fn main() {
             match set_subcommand {
                 Setup::Networking { target_url, rich_api_url, analytics_api_url } => {
                     // setup each networking key based on the value the user inputs.
-                    match target_url {
-                        Some(t) => setup::set_config(ConfigField::TargetUrl ,&t),
-                        None => (),
-                    };
-                    match rich_api_url {
-                        Some(r) => setup::set_config(ConfigField::RichApiUrl ,&r),
-                        None => (),
-                    };
-                    match analytics_api_url {
-                        Some(a) => setup::set_config(ConfigField::AnalyticsApiUrl ,&a),
-                        None => (),
-                    };
+                    if let Some(t) = target_url { setup::set_config(ConfigField::TargetUrl ,&t) }
+                    if let Some(r) = rich_api_url { setup::set_config(ConfigField::RichApiUrl ,&r) }
+                    if let Some(a) = analytics_api_url { setup::set_config(ConfigField::AnalyticsApiUrl ,&a) }
      
                 },
                 Setup::KeyPair { keypair_json_path } => {
@@ -593,46 +1364,94 @@ async fn main() {
                 Crypto::Sign { message } => {
                     operations::crypto::sign(&message);
                 }
+
+                Crypto::ContractAddress { code, from_address, nonce } => {
+                    operations::crypto::contract_address(code, from_address, nonce).await;
+                }
             }
         }
 
         VeryLightCLI::Analyze { analyze_subcommand } => {
             match analyze_subcommand {
-                Analyze::GasPerBlock {  start_time, end_time, window_size, step_size } => {
-                    query(QueryOption::GasPerBlock, vec![
-                        start_time.to_string(), 
-                        end_time.to_string(), 
-                        window_size.to_string(), 
-                        step_size.to_string()
-                    ]).await;                
+                Analyze::GasPerBlock {  start_time, end_time, window_size, step_size, csv, chart, json } => {
+                    let (start_time, end_time, window_size, step_size) = time_parser::parse_window_or_exit(&start_time, &end_time, &window_size, &step_size);
+                    analyze_series(AnalyticsSeries::GasPerBlock, start_time, end_time, window_size, step_size, SeriesOutput { json, chart, csv }).await;
+                },
+                Analyze::MempoolSize { start_time, end_time, window_size, step_size, csv, chart, json } => {
+                    let (start_time, end_time, window_size, step_size) = time_parser::parse_window_or_exit(&start_time, &end_time, &window_size, &step_size);
+                    analyze_series(AnalyticsSeries::MempoolSize, start_time, end_time, window_size, step_size, SeriesOutput { json, chart, csv }).await;
+                },
+                Analyze::Proposers { from, to, slot_time, json, page_size, concurrency } => {
+                    analyze_proposers(PageRange { from, to, page_size, concurrency }, slot_time, json).await;
+                },
+                Analyze::Throughput { from, to, json, page_size, concurrency } => {
+                    analyze_throughput(PageRange { from, to, page_size, concurrency }, json).await;
                 },
-                Analyze::MempoolSize { start_time, end_time, window_size, step_size} => {
-                    query(QueryOption::MempoolSize, vec![
-                        start_time.to_string(), 
-                        end_time.to_string(), 
-                        window_size.to_string(), 
-                        step_size.to_string()
-                    ]).await;
+                Analyze::Fees { from, to, json, page_size, concurrency } => {
+                    analyze_fees(PageRange { from, to, page_size, concurrency }, json).await;
+                },
+                Analyze::SuggestFees { blocks, json } => {
+                    analyze_suggest_fees(blocks, json).await;
                 },
             }          
         },
 
         VeryLightCLI::Parse { parse_subcommand } => {
             match parse_subcommand {
-                Parse::Calldata { json_file } => {
-                    let (output_data_str,_) = args_parser::parse(json_file);
+                Parse::Calldata { json_file, raw } => {
+                    let (output_data_str, output_arguments_str) = args_parser::parse(json_file);
+                    if raw {
+                        let data = match protocol_types::Base64URL::decode(&output_data_str) {
+                            Ok(data) => data,
+                            Err(e) => {
+                                println!("Error: Cannot decode the data of the call. {:?}", e);
+                                std::process::exit(1);
+                            }
+                        };
+                        if let Err(e) = std::io::stdout().write_all(&data) {
+                            println!("Error: Cannot write data to standard output. {}", e);
+                            std::process::exit(1);
+                        }
+                        return;
+                    }
                     println!("Note: Base64 encoded output string for `data` can be used in command `submit tx` and `query account view`.");
                     println!("\n{}\n", output_data_str);
+                    println!("Note: Base64 encoded output string for `arguments` can be used as `deploy-args` in command `submit tx`.");
+                    println!("\n{}\n", output_arguments_str);
                 },
                 Parse::Callback { value, data_type } => {
                     let result = args_parser::from_callback(value, data_type);
                     println!("{}", result);
                 },
-                Parse::Prt { file } => {
-                    let output = prt_parser::parse_file(file);
+                Parse::Prt { file, encoding, data_type, json } => {
+                    let encoding = encoding.event_encoding;
+                    let output = prt_parser::parse_file(file, data_type, json, encoding);
                     println!("{}", output);
+                },
+                Parse::PrtEncode { data_type, json, out } => {
+                    prt_parser::encode_file(json, data_type, out);
                 }
             }
-        }
+        },
+        VeryLightCLI::Export { export_subcommand } => {
+            match export_subcommand {
+                Export::Blocks { from, to, format, out, page_size, concurrency } => {
+                    export(ExportKind::Blocks, format, ExportRange { from, to, page_size, concurrency }, out).await;
+                },
+                Export::Txs { from, to, format, out, page_size, concurrency } => {
+                    export(ExportKind::Txs, format, ExportRange { from, to, page_size, concurrency }, out).await;
+                },
+                Export::Receipts { from, to, format, out, page_size, concurrency } => {
+                    export(ExportKind::Receipts, format, ExportRange { from, to, page_size, concurrency }, out).await;
+                },
+            }
+        },
+        VeryLightCLI::Verify { verify_subcommand } => {
+            match verify_subcommand {
+                Verify::Chain { from, to, page_size, concurrency } => {
+                    verify_chain(from, to, page_size, concurrency).await;
+                },
+            }
+        },
     };
 }
@@ -0,0 +1,229 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::{BTreeMap, HashMap};
+
+use futures::StreamExt;
+
+use crate::{display_types::{FeesReport, ProposerReport, ProposerStats, SlotGap, ThroughputReport}, operations::{fetch, range::PageRange}, setup::{self, ConfigField}, stats::{self, Distribution}};
+
+// Analyze module computes statistics over a range of Blocks on the client side, from the Rich API
+// only. Unlike `analyze gas-per-block` and `analyze mempool-size`, it does not need the analytics API.
+
+// For each block calls `f` with every Block in the range, in increasing block number, and returns
+// the range actually analyzed.
+async fn for_each_block<F: FnMut(protocol_types::Block)>(range: PageRange, mut f: F) -> (u64, u64) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+    let to = match range.to {
+        Some(to) => to,
+        None => fetch::or_exit(fetch::latest_block_number(&client, &rich_api_url).await),
+    };
+    if range.from > to {
+        println!("Error: --from ({}) is greater than --to ({}).", range.from, to);
+        std::process::exit(1);
+    }
+
+    let mut pages = Box::pin(fetch::block_pages(&client, &rich_api_url, range.from, to, range.page_size, range.concurrency));
+    while let Some((_, page)) = pages.next().await {
+        fetch::or_exit(page).into_iter().for_each(&mut f);
+    }
+    (range.from, to)
+}
+
+// Analyze proposers tallies the Blocks proposed by each proposer, and finds gaps in Block timestamps
+// longer than the slot time. The slot time defaults to the median interval between Blocks.
+pub async fn analyze_proposers(range: PageRange, slot_time: Option<u64>, json: bool) {
+    let mut proposers: HashMap<[u8; 32], ProposerStats> = HashMap::new();
+    let mut timestamps: Vec<(u64, u32)> = vec![];
+    let (from, to) = for_each_block(range, |block| {
+        let header = &block.header;
+        let tips: u64 = block.transactions.iter().map(|tx| tx.tip).sum();
+        let stats = proposers.entry(header.proposer_public_key).or_insert_with(|| ProposerStats {
+            proposer_public_key: protocol_types::Base64URL::encode(header.proposer_public_key).to_string(),
+            blocks: 0,
+            share_percent: 0.0,
+            tips: 0,
+            first_block: header.block_number,
+            last_block: header.block_number,
+        });
+        stats.blocks += 1;
+        stats.tips += tips;
+        stats.last_block = header.block_number;
+        timestamps.push((header.block_number, header.timestamp));
+    }).await;
+
+    let total = timestamps.len() as u64;
+    let mut proposers: Vec<ProposerStats> = proposers.into_values().collect();
+    for stats in proposers.iter_mut() {
+        stats.share_percent = stats.blocks as f64 * 100.0 / total as f64;
+    }
+    proposers.sort_by(|a, b| b.blocks.cmp(&a.blocks).then(a.first_block.cmp(&b.first_block)));
+
+    // Only intervals between adjacent Blocks are meaningful.
+    let intervals: Vec<(u64, u32, u32)> = timestamps.windows(2)
+        .filter(|pair| pair[1].0 == pair[0].0 + 1)
+        .map(|pair| (pair[1].0, pair[0].1, pair[1].1))
+        .collect();
+    let slot_time = slot_time.unwrap_or_else(|| {
+        let mut seconds: Vec<u64> = intervals.iter().map(|(_, prev, this)| this.saturating_sub(*prev) as u64).collect();
+        seconds.sort_unstable();
+        seconds.get(seconds.len() / 2).copied().unwrap_or(1)
+    }).max(1);
+    let gaps: Vec<SlotGap> = intervals.iter().filter_map(|(block_number, prev_timestamp, timestamp)| {
+        let interval = timestamp.saturating_sub(*prev_timestamp) as u64;
+        // Slots are counted to the nearest whole slot, so that jitter in timestamps is not reported.
+        let missed_slots = ((interval + slot_time / 2) / slot_time).saturating_sub(1);
+        (missed_slots > 0).then_some(SlotGap { block_number: *block_number, prev_timestamp: *prev_timestamp, timestamp: *timestamp, missed_slots })
+    }).collect();
+
+    let report = ProposerReport {
+        from_block: from,
+        to_block: to,
+        blocks: total,
+        slot_time,
+        missed_slots: gaps.iter().map(|gap| gap.missed_slots).sum(),
+        proposers,
+        gaps,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("Blocks {} to {}: {} Blocks, slot time {}s, {} missed slot(s)", report.from_block, report.to_block, report.blocks, report.slot_time, report.missed_slots);
+    println!();
+    println!("{:<44} {:>8} {:>8} {:>12} {:>12} {:>12}", "Proposer", "Blocks", "Share", "Tips", "First Block", "Last Block");
+    for stats in &report.proposers {
+        println!("{:<44} {:>8} {:>7.2}% {:>12} {:>12} {:>12}", stats.proposer_public_key, stats.blocks, stats.share_percent, stats.tips, stats.first_block, stats.last_block);
+    }
+    if !report.gaps.is_empty() {
+        println!();
+        println!("Gaps:");
+        for gap in &report.gaps {
+            println!("Block {}: {}s after the previous Block ({} missed slot(s))", gap.block_number, gap.timestamp.saturating_sub(gap.prev_timestamp), gap.missed_slots);
+        }
+    }
+}
+
+// Analyze throughput computes Transactions per second, Transactions per Block and the distribution of
+// intervals between Blocks. TPS counts the Transactions in every Block after the first one, over the
+// time between the first and the last Block.
+pub async fn analyze_throughput(range: PageRange, json: bool) {
+    let mut txs_per_block: Vec<f64> = vec![];
+    let mut intervals: Vec<f64> = vec![];
+    let mut histogram: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut prev: Option<(u64, u32)> = None;
+    let mut first_timestamp: Option<u32> = None;
+    let mut last_timestamp = 0;
+    let mut txs_after_first = 0;
+    let (from, to) = for_each_block(range, |block| {
+        let header = &block.header;
+        txs_per_block.push(block.transactions.len() as f64);
+        if let Some((prev_number, prev_timestamp)) = prev {
+            txs_after_first += block.transactions.len() as u64;
+            if header.block_number == prev_number + 1 {
+                let interval = header.timestamp.saturating_sub(prev_timestamp) as u64;
+                intervals.push(interval as f64);
+                *histogram.entry(interval).or_insert(0) += 1;
+            }
+        }
+        first_timestamp.get_or_insert(header.timestamp);
+        last_timestamp = header.timestamp;
+        prev = Some((header.block_number, header.timestamp));
+    }).await;
+
+    let duration_secs = first_timestamp.map(|first| last_timestamp.saturating_sub(first) as u64).unwrap_or(0);
+    let report = ThroughputReport {
+        from_block: from,
+        to_block: to,
+        blocks: txs_per_block.len() as u64,
+        transactions: txs_per_block.iter().sum::<f64>() as u64,
+        duration_secs,
+        tps: if duration_secs > 0 { txs_after_first as f64 / duration_secs as f64 } else { 0.0 },
+        txs_per_block: stats::distribution(&txs_per_block),
+        block_interval_secs: stats::distribution(&intervals),
+        block_interval_histogram: histogram,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("Blocks {} to {}: {} Blocks, {} Transactions in {}s", report.from_block, report.to_block, report.blocks, report.transactions, report.duration_secs);
+    println!("TPS: {:.3}", report.tps);
+    println!("Transactions per Block: {}", display_distribution(&report.txs_per_block));
+    println!("Block interval (s): {}", display_distribution(&report.block_interval_secs));
+    if !report.block_interval_histogram.is_empty() {
+        println!();
+        println!("{:>12} {:>8}", "Interval (s)", "Blocks");
+        for (interval, count) in &report.block_interval_histogram {
+            println!("{:>12} {:>8}", interval, count);
+        }
+    }
+}
+
+// Analyze fees computes the distributions of gas_price and tip, and how much of its gas_limit each
+// Transaction consumed.
+pub async fn analyze_fees(range: PageRange, json: bool) {
+    let mut gas_prices: Vec<f64> = vec![];
+    let mut tips: Vec<f64> = vec![];
+    let mut utilization: Vec<f64> = vec![];
+    let mut total_gas_consumed = 0;
+    let mut total_gas_limit = 0;
+    let (from, to) = for_each_block(range, |block| {
+        for (tx, receipt) in block.transactions.iter().zip(block.receipts.iter()) {
+            gas_prices.push(tx.gas_price as f64);
+            tips.push(tx.tip as f64);
+            if tx.gas_limit > 0 {
+                utilization.push(receipt.gas_consumed as f64 * 100.0 / tx.gas_limit as f64);
+            }
+            total_gas_consumed += receipt.gas_consumed;
+            total_gas_limit += tx.gas_limit;
+        }
+    }).await;
+
+    let report = FeesReport {
+        from_block: from,
+        to_block: to,
+        transactions: gas_prices.len() as u64,
+        gas_price: stats::distribution(&gas_prices),
+        tip: stats::distribution(&tips),
+        gas_utilization_percent: stats::distribution(&utilization),
+        total_gas_consumed,
+        total_gas_limit,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("Blocks {} to {}: {} Transactions", report.from_block, report.to_block, report.transactions);
+    println!("Gas price: {}", display_distribution(&report.gas_price));
+    println!("Tip: {}", display_distribution(&report.tip));
+    println!("Gas utilization (%): {}", display_distribution(&report.gas_utilization_percent));
+    if report.total_gas_limit > 0 {
+        println!("Total gas consumed: {} of {} ({:.2}%)", report.total_gas_consumed, report.total_gas_limit,
+            report.total_gas_consumed as f64 * 100.0 / report.total_gas_limit as f64);
+    }
+}
+
+fn display_distribution(distribution: &Option<Distribution>) -> String {
+    match distribution {
+        Some(distribution) => distribution.to_string(),
+        None => "no data".to_string(),
+    }
+}
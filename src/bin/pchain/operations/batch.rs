@@ -0,0 +1,378 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::{cell::RefCell, collections::HashMap, fs::{File, OpenOptions}, io::Write, path::Path};
+
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{input, operations::{fetch, query, submit::{self, SubmitTxJson}}, setup::{self, ConfigField}};
+
+// Batch module submits many Transactions listed in a JSON lines file, one Transaction per line.
+//
+// Nonces are assigned in file order, per sending address, starting from its on-chain nonce. Every
+// outcome is appended to a results file as a JSON line. A line is bound to its nonce as soon as it is
+// signed, and the binding is recorded before the Transaction is sent. Running the same batch again skips
+// lines which were submitted, and re-sends the others with the nonce they were bound to, so that gaps left
+// by failed lines are filled in. If the on-chain nonce has moved past the nonce of a line which was not
+// submitted, the line is skipped if its Transaction is on chain, and bound to a new nonce otherwise, as its
+// nonce can no longer be used.
+//
+// The Transactions of one sender are sent one at a time in nonce order, and the rest of them are not sent
+// once one fails.
+
+// One line of the batch file. It has the fields of the `submit tx-from` file except `nonce`, which is assigned by the batch.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BatchTxJson {
+    pub from_address: String,
+    pub to_address: String,
+    pub value: u64,
+    pub tip: u64,
+    pub gas_limit: u64,
+    pub gas_price: u64,
+    #[serde(default)]
+    pub data: String,
+    #[serde(default)]
+    pub deploy_args: String,
+    pub path_to_keypair_json: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BatchStatus {
+    // Signed with its nonce, but the outcome of sending it is not known.
+    Signed,
+    Submitted,
+    Failed,
+}
+
+// One line of the results file. The last record of a line in the batch file is its current state.
+#[derive(Serialize, Deserialize)]
+struct BatchRecord {
+    line: usize,
+    from_address: String,
+    nonce: u64,
+    hash: String,
+    status: BatchStatus,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    detail: Option<String>,
+}
+
+// Submit batch signs and submits every Transaction in `file` which has not been submitted by an earlier run,
+// submitting the Transactions of up to `concurrency` senders at the same time. Results are appended to `results`,
+// which defaults to `<file>.results.jsonl`.
+pub async fn submit_batch(file: String, results: Option<String>, concurrency: usize) {
+    let results = match results {
+        Some(results) => results,
+        None if input::is_stdin(&file) => {
+            println!("Error: 'results' is required when the batch is read from standard input.");
+            std::process::exit(1);
+        },
+        None => format!("{}.results.jsonl", file),
+    };
+    let entries = read_batch_file(&file);
+    let mut records = read_records(&results);
+
+    let mut chain_nonces: HashMap<String, u64> = HashMap::new();
+    for (line, entry) in &entries {
+        if let Some(record) = records.get(line) {
+            if record.from_address != entry.from_address {
+                println!("Error: Line {} of {} is from {}, but the results file {} records it from {}. Use a new results file for a changed batch.",
+                    line, file, entry.from_address, results, record.from_address);
+                std::process::exit(1);
+            }
+        }
+        if !chain_nonces.contains_key(&entry.from_address) {
+            chain_nonces.insert(entry.from_address.clone(), query::query_nonce(&entry.from_address).await);
+        }
+    }
+
+    // Every remaining line is signed, and its nonce recorded, before anything is sent.
+    let mut results_file = match OpenOptions::new().create(true).append(true).open(&results) {
+        Ok(results_file) => results_file,
+        Err(e) => {
+            println!("Error: Cannot open results file {}. {}", results, e);
+            std::process::exit(1);
+        }
+    };
+    // A record cut short by an interruption must not run into the next one.
+    if std::fs::read(&results).map(|content| matches!(content.last(), Some(byte) if *byte != b'\n')).unwrap_or(false) {
+        append_line(&mut results_file, &results, "");
+    }
+
+    // Lines whose nonce has been used on chain since they were signed were either included, or can no longer be.
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+    for record in records.values_mut() {
+        if record.status != BatchStatus::Submitted && record.nonce < chain_nonces.get(&record.from_address).copied().unwrap_or(0)
+            && fetch::or_exit(fetch::transaction_included(&client, &rich_api_url, &record.hash).await) {
+            println!("Line {}: found on chain with nonce {}, hash {}", record.line, record.nonce, record.hash);
+            record.status = BatchStatus::Submitted;
+            record.detail = None;
+            append_record(&mut results_file, &results, record);
+        }
+    }
+
+    let nonces = assign_nonces(&entries, &records, &chain_nonces);
+    let mut skipped = 0;
+    let mut signed: Vec<(BatchRecord, Vec<u8>)> = vec![];
+    for ((line, entry), nonce) in entries.into_iter().zip(nonces) {
+        let nonce = match nonce {
+            Some(nonce) => nonce,
+            None => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let from_address = entry.from_address.clone();
+        let is_deploy = &entry.to_address == "null"; // To address is null if and only if it is a deploy transaction
+        let (tx_data, hash) = match submit::sign_tx_json(into_submit_tx_json(entry, nonce), is_deploy) {
+            Ok(signed) => signed,
+            Err(e) => {
+                println!("Error: Cannot sign the Transaction on line {}. {}", line, e);
+                std::process::exit(1);
+            }
+        };
+        let hash = protocol_types::Base64URL::encode(hash).to_string();
+        let record = BatchRecord { line, from_address, nonce, hash, status: BatchStatus::Signed, detail: None };
+        append_record(&mut results_file, &results, &record);
+        signed.push((record, tx_data));
+    }
+
+    let api_url = format!("{}/transaction", setup::read_config(ConfigField::TargetUrl));
+    let results_file = RefCell::new(results_file);
+    let statuses: Vec<Vec<BatchStatus>> = stream::iter(sender_queues(signed).into_iter().map(|queue| {
+        let (client, api_url, results_file, results) = (&client, &api_url, &results_file, &results);
+        async move {
+            let mut statuses = vec![];
+            let mut failed_line = None;
+            for (mut record, tx_data) in queue {
+                let outcome = match failed_line {
+                    Some(failed_line) => Err(format!("Not sent, because line {} with a lower nonce failed.", failed_line)),
+                    None => submit::send_transaction(client, tx_data, api_url).await,
+                };
+                match outcome {
+                    Ok(_) => {
+                        println!("Line {}: submitted with nonce {}, hash {}", record.line, record.nonce, record.hash);
+                        record.status = BatchStatus::Submitted;
+                    },
+                    Err(e) => {
+                        println!("Line {}: failed with nonce {}, hash {}. {}", record.line, record.nonce, record.hash, e);
+                        failed_line = failed_line.or(Some(record.line));
+                        record.status = BatchStatus::Failed;
+                        record.detail = Some(e);
+                    }
+                }
+                append_record(&mut results_file.borrow_mut(), results, &record);
+                statuses.push(record.status);
+            }
+            statuses
+        }
+    })).buffer_unordered(concurrency.max(1)).collect().await;
+
+    let submitted = statuses.iter().flatten().filter(|status| **status == BatchStatus::Submitted).count();
+    let failed = statuses.iter().flatten().filter(|status| **status == BatchStatus::Failed).count();
+    println!("Submitted {} Transaction(s), {} failed, {} skipped as already submitted. Results are saved to {}", submitted, failed, skipped, results);
+    if failed > 0 {
+        println!("Error: {} Transaction(s) failed. Run the same command again to re-send them.", failed);
+        std::process::exit(1);
+    }
+}
+
+// Assign nonces returns the nonce to send each line of `entries` with, or None for lines which are already submitted.
+// A line keeps the nonce recorded for it unless the on-chain nonce in `chain_nonces` has moved past it. Other lines
+// get the next nonces of their sender, after its on-chain nonce and the nonces which are kept.
+fn assign_nonces(entries: &[(usize, BatchTxJson)], records: &HashMap<usize, BatchRecord>, chain_nonces: &HashMap<String, u64>) -> Vec<Option<u64>> {
+    let mut next_nonces = chain_nonces.clone();
+    for record in records.values() {
+        if let Some(next_nonce) = next_nonces.get_mut(&record.from_address) {
+            *next_nonce = (*next_nonce).max(record.nonce + 1);
+        }
+    }
+    entries.iter().map(|(line, entry)| {
+        let chain_nonce = chain_nonces.get(&entry.from_address).copied().unwrap_or(0);
+        match records.get(line) {
+            Some(record) if record.status == BatchStatus::Submitted => None,
+            Some(record) if record.nonce >= chain_nonce => Some(record.nonce),
+            _ => {
+                let next_nonce = next_nonces.entry(entry.from_address.clone()).or_insert(chain_nonce);
+                *next_nonce += 1;
+                Some(*next_nonce - 1)
+            }
+        }
+    }).collect()
+}
+
+// Sender queues groups signed Transactions by sender, in nonce order, so that each sender's Transactions can be sent in order.
+fn sender_queues(signed: Vec<(BatchRecord, Vec<u8>)>) -> Vec<Vec<(BatchRecord, Vec<u8>)>> {
+    let mut queues: Vec<Vec<(BatchRecord, Vec<u8>)>> = vec![];
+    for (record, tx_data) in signed {
+        match queues.iter_mut().find(|queue| queue[0].0.from_address == record.from_address) {
+            Some(queue) => queue.push((record, tx_data)),
+            None => queues.push(vec![(record, tx_data)]),
+        }
+    }
+    for queue in &mut queues {
+        queue.sort_by_key(|(record, _)| record.nonce);
+    }
+    queues
+}
+
+fn into_submit_tx_json(entry: BatchTxJson, nonce: u64) -> SubmitTxJson {
+    SubmitTxJson {
+        from_address: entry.from_address,
+        to_address: entry.to_address,
+        value: entry.value,
+        tip: entry.tip,
+        gas_limit: entry.gas_limit,
+        gas_price: entry.gas_price,
+        data: entry.data,
+        deploy_args: entry.deploy_args,
+        nonce,
+        path_to_keypair_json: entry.path_to_keypair_json,
+    }
+}
+
+// Read batch file returns the Transactions in `file` (or standard input if it is `-`) with their (1-based) line numbers. Blank lines are skipped.
+fn read_batch_file(file: &str) -> Vec<(usize, BatchTxJson)> {
+    let content = match input::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Error: Cannot read batch file {}. {}", file, e);
+            std::process::exit(1);
+        }
+    };
+    let mut entries = vec![];
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<BatchTxJson>(line) {
+            Ok(entry) => entries.push((i + 1, entry)),
+            Err(e) => {
+                println!("Error: Line {} of {} is not a valid Transaction. {}", i + 1, file, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    entries
+}
+
+// Read records returns the last record of every line in the results file, if it exists.
+fn read_records(results: &str) -> HashMap<usize, BatchRecord> {
+    let mut records = HashMap::new();
+    if !Path::new(results).exists() {
+        return records;
+    }
+    let content = match std::fs::read_to_string(results) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Error: Cannot read results file {}. {}", results, e);
+            std::process::exit(1);
+        }
+    };
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        // A record cut short by an interruption is ignored.
+        if let Ok(record) = serde_json::from_str::<BatchRecord>(line) {
+            records.insert(record.line, record);
+        }
+    }
+    records
+}
+
+fn append_record(results_file: &mut File, results: &str, record: &BatchRecord) {
+    append_line(results_file, results, &serde_json::to_string(record).unwrap());
+}
+
+fn append_line(results_file: &mut File, results: &str, line: &str) {
+    let result = writeln!(results_file, "{}", line).and_then(|_| results_file.sync_data());
+    if let Err(e) = result {
+        println!("Error: Cannot write results file {}. {}", results, e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{BatchRecord, BatchStatus, BatchTxJson};
+
+    fn entry(from_address: &str) -> BatchTxJson {
+        BatchTxJson {
+            from_address: from_address.to_string(),
+            to_address: "to".to_string(),
+            value: 1,
+            tip: 0,
+            gas_limit: 0,
+            gas_price: 0,
+            data: String::new(),
+            deploy_args: String::new(),
+            path_to_keypair_json: String::new(),
+        }
+    }
+
+    fn record(line: usize, from_address: &str, nonce: u64, status: BatchStatus) -> (usize, BatchRecord) {
+        (line, BatchRecord { line, from_address: from_address.to_string(), nonce, hash: String::new(), status, detail: None })
+    }
+
+    fn chain_nonces(nonces: &[(&str, u64)]) -> HashMap<String, u64> {
+        nonces.iter().map(|(address, nonce)| (address.to_string(), *nonce)).collect()
+    }
+
+    #[test]
+    fn test_assign_nonces() {
+        // Nonces are sequential per sender, in file order.
+        let entries = vec![(1, entry("a")), (2, entry("b")), (3, entry("a")), (5, entry("a"))];
+        let nonces = super::assign_nonces(&entries, &HashMap::new(), &chain_nonces(&[("a", 7), ("b", 0)]));
+        assert_eq!(nonces, vec![Some(7), Some(0), Some(8), Some(9)]);
+    }
+
+    #[test]
+    fn test_assign_nonces_resume() {
+        let entries = vec![(1, entry("a")), (2, entry("a")), (3, entry("a")), (4, entry("a")), (5, entry("a"))];
+
+        // Submitted lines are skipped, and lines which failed or were interrupted keep their nonce.
+        // New lines continue after every nonce used so far.
+        let records: HashMap<usize, BatchRecord> = vec![
+            record(1, "a", 7, BatchStatus::Submitted),
+            record(2, "a", 8, BatchStatus::Failed),
+            record(3, "a", 9, BatchStatus::Signed),
+        ].into_iter().collect();
+        let nonces = super::assign_nonces(&entries, &records, &chain_nonces(&[("a", 8)]));
+        assert_eq!(nonces, vec![None, Some(8), Some(9), Some(10), Some(11)]);
+
+        // Once the on-chain nonce moved past the nonce of a line which is not submitted, the line gets a new nonce.
+        let nonces = super::assign_nonces(&entries, &records, &chain_nonces(&[("a", 9)]));
+        assert_eq!(nonces, vec![None, Some(10), Some(9), Some(11), Some(12)]);
+        let nonces = super::assign_nonces(&entries, &records, &chain_nonces(&[("a", 12)]));
+        assert_eq!(nonces, vec![None, Some(12), Some(13), Some(14), Some(15)]);
+    }
+
+    #[test]
+    fn test_sender_queues() {
+        let signed = vec![
+            (record(1, "a", 10, BatchStatus::Signed).1, vec![1]),
+            (record(2, "b", 0, BatchStatus::Signed).1, vec![2]),
+            (record(3, "a", 8, BatchStatus::Signed).1, vec![3]),
+            (record(4, "a", 11, BatchStatus::Signed).1, vec![4]),
+        ];
+        let queues: Vec<Vec<usize>> = super::sender_queues(signed).into_iter()
+            .map(|queue| queue.into_iter().map(|(record, _)| record.line).collect())
+            .collect();
+        assert_eq!(queues, vec![vec![3, 1, 4], vec![2]]);
+    }
+}
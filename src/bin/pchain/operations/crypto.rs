@@ -22,7 +22,7 @@ use rand::rngs::OsRng;
 use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use serde::{Serialize, Deserialize};
-use crate::setup;
+use crate::{setup, operations::{submit, query}};
 
 const KEYPAIR_LENGTH: usize = 64;
 const PRIVATEKEY_LENGTH: usize = 32;
@@ -81,10 +81,24 @@ pub(crate) fn sign(message: &str) {
         ed25519_dalek::Keypair::from_bytes(&keypair_bs)
             .expect(E_MSG_JSON_INVALID_FORMAT)
     };
-    let serialized_credentials = protocol_types::Base64URL::decode(&message).unwrap();
+    let serialized_credentials = protocol_types::Base64URL::decode(message).unwrap();
     let ciphertext : ed25519_dalek::Signature = keypair.sign(&serialized_credentials[..]);
     let ciphertext = protocol_types::Base64URL::encode(ciphertext);
 
     println!("Message: {}", message);
-    println!("Ciphertext: {}", ciphertext.to_string());
+    println!("Ciphertext: {}", ciphertext);
+}
+
+// Predict the address of a contract deployed from `from_address`. If no nonce is given, the next
+// on-chain nonce of `from_address` is used, i.e. the address the contract gets if it is deployed next.
+pub(crate) async fn contract_address(code: String, from_address: String, nonce: Option<u64>) {
+    let contract_code = submit::load_contract_code(code);
+    let sender_address = submit::parse_sender_address(from_address.clone());
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None => query::query_nonce(&from_address).await,
+    };
+
+    println!("Nonce: {}", nonce);
+    println!("Contract address: \"{}\"", submit::contract_address(&contract_code, &sender_address, nonce));
 }
@@ -0,0 +1,89 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use futures::StreamExt;
+
+use crate::{args_parser, display_types::{self, BytesEncoding, EncodedFrom, EventRecord}, operations::fetch, setup::{self, ConfigField}, Base64Address};
+
+// Events module searches the Events emitted in the Receipts of a range of blocks.
+// Blocks are walked page by page through the Rich API, so that ranges of any size can be searched.
+
+pub struct EventFilter {
+    pub topic_prefix: Option<String>,
+    pub contract: Option<Base64Address>,
+    pub value_type: Option<String>,
+}
+
+pub async fn query_events(from_block: u64, to_block: Option<u64>, filter: EventFilter, json: bool, encoding: BytesEncoding) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+
+    let contract = filter.contract.as_ref().map(|address| match protocol_types::Base64URL::decode(address) {
+        Ok(address) if address.len() == 32 => address,
+        _ => {
+            println!("Error: 'contract' must be a Base64 encoded address of 32 bytes.");
+            std::process::exit(1);
+        }
+    });
+
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => fetch::or_exit(fetch::latest_block_number(&client, &rich_api_url).await),
+    };
+
+    let mut matches = 0;
+    let mut pages = Box::pin(fetch::block_pages(&client, &rich_api_url, from_block, to_block, fetch::SCAN_PAGE_SIZE, 1));
+    while let Some((_, page)) = pages.next().await {
+        for block in fetch::or_exit(page) {
+            for (tx, receipt) in block.transactions.iter().zip(block.receipts.iter()) {
+                if let Some(contract) = &contract {
+                    if tx.to_address[..] != contract[..] {
+                        continue;
+                    }
+                }
+                for event in &receipt.events {
+                    if let Some(prefix) = &filter.topic_prefix {
+                        if !event.topic.starts_with(prefix.as_bytes()) {
+                            continue;
+                        }
+                    }
+                    fetch::print_item(&event_record(block.header.block_number, tx, event, &filter.value_type, encoding), json);
+                    matches += 1;
+                }
+            }
+        }
+    }
+
+    if !json {
+        println!("{} event(s) found in blocks {} to {}.", matches, from_block, to_block);
+    }
+}
+
+fn event_record(block_number: u64, tx: &protocol_types::Transaction, event: &protocol_types::Event, value_type: &Option<String>, encoding: BytesEncoding) -> EventRecord {
+    let decoded_value = value_type.as_ref().map(|data_type| match args_parser::decode_borsh_value(&event.value, data_type) {
+        Ok(value) => value,
+        Err(e) => format!("(not decodable as {}: {})", data_type, e),
+    });
+    let display_event = display_types::Event::encoded_from(event.clone(), encoding);
+    EventRecord {
+        block_number,
+        tx_hash: protocol_types::Base64URL::encode(tx.hash).to_string(),
+        contract_address: protocol_types::Base64URL::encode(tx.to_address).to_string(),
+        topic: display_event.topic,
+        value: display_event.value,
+        decoded_value,
+    }
+}
@@ -0,0 +1,418 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::{fs::{self, File, OpenOptions}, io::{Seek, SeekFrom, Write}, path::{Path, PathBuf}, str::FromStr};
+
+use futures::StreamExt;
+use protocol_types::Serializable;
+use serde::{Deserialize, Serialize};
+
+use crate::{display_types::{self, BytesEncoding, EncodedFrom, ReceiptRecord}, operations::fetch, setup::{self, ConfigField}};
+
+// Export module writes Blocks, Transactions or Receipts in a range of numbers to files in a directory.
+// After every page, the last written number is saved to a progress file next to the output, so that
+// running the same command again after an interruption continues where the previous run stopped.
+// The progress file records the requested range, and an export is only resumed for the same range.
+
+pub enum ExportKind {
+    Blocks,
+    Txs,
+    Receipts,
+}
+
+impl ExportKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ExportKind::Blocks => "blocks",
+            ExportKind::Txs => "txs",
+            ExportKind::Receipts => "receipts",
+        }
+    }
+
+    // Data type extension understood by `parse prt`.
+    fn prt_extension(&self) -> &'static str {
+        match self {
+            ExportKind::Blocks => "blk",
+            ExportKind::Txs => "tx",
+            ExportKind::Receipts => "recp",
+        }
+    }
+
+    fn csv_header(&self) -> &'static [&'static str] {
+        match self {
+            ExportKind::Blocks => &["block_number", "blockchain_id", "block_version_number", "timestamp", "this_block_hash", "prev_block_hash",
+                "txs_hash", "state_hash", "receipts_hash", "proposer_public_key", "tx_count"],
+            ExportKind::Txs => &["tx_num", "hash", "from_address", "to_address", "value", "tip", "gas_limit", "gas_price",
+                "n_txs_on_chain_from_address", "data"],
+            ExportKind::Receipts => &["tx_num", "tx_hash", "status_code", "gas_consumed", "return_value", "event_count"],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+    Prt,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "csv" => Ok(ExportFormat::Csv),
+            "prt" => Ok(ExportFormat::Prt),
+            _ => Err(format!("unknown format `{}`. Expected one of jsonl, csv, prt", s)),
+        }
+    }
+}
+
+impl ExportFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Prt => "prt",
+        }
+    }
+}
+
+pub struct ExportRange {
+    pub from: u64,
+    // Defaults to the latest Block or Transaction.
+    pub to: Option<u64>,
+    pub page_size: u64,
+    pub concurrency: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Progress {
+    // Range requested by the run which started the export.
+    from: u64,
+    to: Option<u64>,
+    last_written: u64,
+    // Length of the jsonl or csv output file after `last_written` was written.
+    file_len: u64,
+}
+
+// Item is one exported record, as returned by the Rich API.
+enum Item {
+    Block(protocol_types::Block),
+    Tx(u64, protocol_types::Transaction, protocol_types::Receipt),
+}
+
+impl Item {
+    fn number(&self) -> u64 {
+        match self {
+            Item::Block(block) => block.header.block_number,
+            Item::Tx(tx_num, _, _) => *tx_num,
+        }
+    }
+
+    fn into_json(self, kind: &ExportKind) -> String {
+        match (self, kind) {
+            (Item::Block(block), _) => serde_json::to_string(&display_types::Block::encoded_from(block, BytesEncoding::Auto)),
+            (Item::Tx(tx_num, tx, receipt), ExportKind::Receipts) => serde_json::to_string(&ReceiptRecord {
+                tx_num,
+                tx_hash: protocol_types::Base64URL::encode(tx.hash).to_string(),
+                receipt: display_types::Receipt::encoded_from(receipt, BytesEncoding::Auto),
+            }),
+            (Item::Tx(tx_num, tx, receipt), _) => serde_json::to_string(&display_types::TransactionWithReceipt {
+                tx_num,
+                transaction: display_types::Transaction::from(tx),
+                receipt: display_types::Receipt::encoded_from(receipt, BytesEncoding::Auto),
+            }),
+        }.unwrap()
+    }
+
+    fn into_csv(self, kind: &ExportKind) -> Vec<String> {
+        match (self, kind) {
+            (Item::Block(block), _) => {
+                let tx_count = block.transactions.len();
+                let header = display_types::BlockHeader::from(block.header);
+                vec![header.block_number.to_string(), header.blockchain_id.to_string(), header.block_version_number.to_string(),
+                    header.timestamp, header.this_block_hash, header.prev_block_hash, header.txs_hash, header.state_hash,
+                    header.receipts_hash, header.proposer_public_key, tx_count.to_string()]
+            },
+            (Item::Tx(tx_num, tx, receipt), ExportKind::Receipts) => {
+                let tx_hash = protocol_types::Base64URL::encode(tx.hash).to_string();
+                let receipt = display_types::Receipt::encoded_from(receipt, BytesEncoding::Auto);
+                vec![tx_num.to_string(), tx_hash, format!("{:?}", receipt.status_code), receipt.gas_consumed.to_string(),
+                    receipt.return_value, receipt.events.len().to_string()]
+            },
+            (Item::Tx(tx_num, tx, _), _) => {
+                let tx = display_types::Transaction::from(tx);
+                vec![tx_num.to_string(), tx.hash, tx.from_address, tx.to_address, tx.value.to_string(), tx.tip.to_string(),
+                    tx.gas_limit.to_string(), tx.gas_price.to_string(), tx.n_txs_on_chain_from_address.to_string(), tx.data]
+            },
+        }
+    }
+
+    fn into_prt(self, kind: &ExportKind) -> Vec<u8> {
+        match (self, kind) {
+            (Item::Block(block), _) => protocol_types::Block::serialize(&block),
+            (Item::Tx(_, _, receipt), ExportKind::Receipts) => protocol_types::Receipt::serialize(&receipt),
+            (Item::Tx(_, tx, _), _) => protocol_types::Transaction::serialize(&tx),
+        }
+    }
+}
+
+// Export writes the items numbered `range.from` to `range.to` (inclusive) into the directory `out`.
+pub async fn export(kind: ExportKind, format: ExportFormat, range: ExportRange, out: String) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+
+    let out = PathBuf::from(out);
+    if let Err(e) = fs::create_dir_all(&out) {
+        println!("Error: Cannot create output directory {}. {}", out.display(), e);
+        std::process::exit(1);
+    }
+    let mut exporter = Exporter::open(kind, format, &out, range.from, range.to);
+
+    let to = match range.to {
+        Some(to) => to,
+        None => match exporter.kind {
+            ExportKind::Blocks => fetch::or_exit(fetch::latest_block_number(&client, &rich_api_url).await),
+            ExportKind::Txs | ExportKind::Receipts => fetch::or_exit(fetch::latest_tx_number(&client, &rich_api_url).await),
+        },
+    };
+    let from = exporter.resume_from();
+    if from > to {
+        println!("Nothing to export. {} are already exported up to number {}.", exporter.kind.name(), from.saturating_sub(1));
+        return;
+    }
+    if from != range.from {
+        println!("Resuming export of {} from number {}.", exporter.kind.name(), from);
+    }
+
+    let export_blocks = matches!(exporter.kind, ExportKind::Blocks);
+    let mut pages = Box::pin(fetch::paged(from, to, range.page_size, range.concurrency, |start, window| {
+        let (client, rich_api_url) = (&client, &rich_api_url);
+        async move {
+            let selector = format!("num={}", start);
+            Ok(if export_blocks {
+                fetch::blocks(client, rich_api_url, &selector, window).await?
+                    .into_iter().map(Item::Block).collect()
+            } else {
+                fetch::transactions(client, rich_api_url, &selector, window).await?
+                    .into_iter().map(|(tx_num, tx, receipt)| Item::Tx(tx_num, tx, receipt)).collect::<Vec<Item>>()
+            })
+        }
+    }));
+    let mut exported = 0;
+    while let Some(((start, window), page)) = pages.next().await {
+        let result = page.and_then(|items| {
+            let mut items: Vec<Item> = items.into_iter().filter(|item| fetch::in_page((start, window), item.number())).collect();
+            items.sort_by_key(|item| item.number());
+            if items.len() as u64 != window || items.iter().enumerate().any(|(i, item)| item.number() != start + i as u64) {
+                return Err(format!("Rich API returned {} of the {} items from number {}", items.len(), window, start));
+            }
+            exporter.write_page(items)
+        });
+        if let Err(e) = result {
+            println!("Error: {}", e);
+            println!("Run the same command again to resume the export from number {}.", start);
+            std::process::exit(1);
+        }
+        exported += window;
+    }
+    println!("Exported {} {} (number {} to {}) to {}.", exported, exporter.kind.name(), from, to, out.display());
+}
+
+struct Exporter {
+    kind: ExportKind,
+    format: ExportFormat,
+    dir: PathBuf,
+    // Output file of the jsonl and csv formats.
+    file: Option<File>,
+    progress_path: PathBuf,
+    progress: Option<Progress>,
+    // Requested range, see `Progress`.
+    from: u64,
+    to: Option<u64>,
+}
+
+impl Exporter {
+    // Open prepares the output directory for writing. If a progress file of a previous run exists,
+    // partially written output after the last recorded item is discarded. It exits if the previous
+    // run exported a different range.
+    fn open(kind: ExportKind, format: ExportFormat, dir: &Path, from: u64, to: Option<u64>) -> Exporter {
+        let progress_path = dir.join(format!(".{}.{}.progress", kind.name(), format.name()));
+        let progress: Option<Progress> = match fs::read_to_string(&progress_path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(progress) => Some(progress),
+                Err(e) => {
+                    println!("Error: Progress file {} is corrupted. {}", progress_path.display(), e);
+                    std::process::exit(1);
+                }
+            },
+            Err(_) => None,
+        };
+        if let Some(progress) = &progress {
+            if (progress.from, progress.to) != (from, to) {
+                let range = |from: u64, to: Option<u64>| format!("--from {}{}", from, to.map(|to| format!(" --to {}", to)).unwrap_or_default());
+                println!("Error: An export of {} with {} is in progress in {}. Run it again with the same range to resume, or choose another --out.",
+                    kind.name(), range(progress.from, progress.to), dir.display());
+                std::process::exit(1);
+            }
+        }
+
+        let file = if format == ExportFormat::Prt {
+            None
+        } else {
+            let path = dir.join(format!("{}.{}", kind.name(), format.name()));
+            if progress.is_none() && fs::metadata(&path).map(|m| m.len() > 0).unwrap_or(false) {
+                println!("Error: {} already exists and no export is in progress there. Remove it or choose another --out.", path.display());
+                std::process::exit(1);
+            }
+            let file = OpenOptions::new().create(true).write(true).truncate(false).open(&path)
+                .and_then(|file| file.set_len(progress.as_ref().map(|p| p.file_len).unwrap_or(0)).map(|_| file));
+            match file {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    println!("Error: Cannot open {}. {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            }
+        };
+
+        Exporter { kind, format, dir: dir.to_path_buf(), file, progress_path, progress, from, to }
+    }
+
+    // Resume from returns the first number which is not exported yet.
+    fn resume_from(&self) -> u64 {
+        match &self.progress {
+            Some(progress) => progress.last_written.saturating_add(1),
+            None => self.from,
+        }
+    }
+
+    fn write_page(&mut self, items: Vec<Item>) -> Result<(), String> {
+        let last_written = match items.last() {
+            Some(item) => item.number(),
+            None => return Ok(()),
+        };
+
+        let file_len = match &mut self.file {
+            Some(file) => {
+                let mut content = String::new();
+                if self.format == ExportFormat::Csv && self.progress.is_none() {
+                    content.push_str(&self.kind.csv_header().join(","));
+                    content.push('\n');
+                }
+                for item in items {
+                    match self.format {
+                        ExportFormat::Csv => content.push_str(&item.into_csv(&self.kind).iter().map(|field| csv_field(field)).collect::<Vec<String>>().join(",")),
+                        _ => content.push_str(&item.into_json(&self.kind)),
+                    }
+                    content.push('\n');
+                }
+                file.seek(SeekFrom::End(0))
+                    .and_then(|_| file.write_all(content.as_bytes()))
+                    .and_then(|_| file.sync_data())
+                    .and_then(|_| file.seek(SeekFrom::End(0)))
+                    .map_err(|e| format!("Fail to write output file. {}", e))?
+            },
+            None => {
+                for item in items {
+                    let path = self.dir.join(format!("{}.{}.prt", item.number(), self.kind.prt_extension()));
+                    fs::write(&path, item.into_prt(&self.kind)).map_err(|e| format!("Fail to write {}. {}", path.display(), e))?;
+                }
+                0
+            },
+        };
+
+        // Write the progress to a temporary file first, so that an interruption never leaves it half written.
+        let progress = Progress { from: self.from, to: self.to, last_written, file_len };
+        let tmp_path = self.progress_path.with_extension("progress.tmp");
+        fs::write(&tmp_path, serde_json::to_string(&progress).unwrap())
+            .and_then(|_| fs::rename(&tmp_path, &self.progress_path))
+            .map_err(|e| format!("Fail to save progress to {}. {}", self.progress_path.display(), e))?;
+        self.progress = Some(progress);
+        Ok(())
+    }
+}
+
+// Csv field quotes a field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Write, path::PathBuf};
+
+    use crate::operations::fixtures;
+
+    use super::{ExportFormat, ExportKind, Exporter, Item};
+
+    fn tx_item(tx_num: u64) -> Item {
+        Item::Tx(tx_num, protocol_types::Transaction {
+            to_address: [1u8; 32],
+            value: tx_num,
+            hash: [tx_num as u8; 32],
+            ..fixtures::transaction()
+        }, fixtures::receipt())
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pchain-export-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resume() {
+        let dir = test_dir("resume");
+        let mut exporter = Exporter::open(ExportKind::Txs, ExportFormat::Csv, &dir, 5, Some(9));
+        assert_eq!(exporter.resume_from(), 5);
+        exporter.write_page(vec![tx_item(5), tx_item(6)]).unwrap();
+        assert_eq!(exporter.resume_from(), 7);
+        let written = fs::read_to_string(dir.join("txs.csv")).unwrap();
+        drop(exporter);
+
+        // A page which was interrupted before its progress was saved is discarded on resume.
+        let mut file = fs::OpenOptions::new().append(true).open(dir.join("txs.csv")).unwrap();
+        file.write_all(b"7,partial").unwrap();
+        let mut exporter = Exporter::open(ExportKind::Txs, ExportFormat::Csv, &dir, 5, Some(9));
+        assert_eq!(exporter.resume_from(), 7);
+        assert_eq!(fs::read_to_string(dir.join("txs.csv")).unwrap(), written);
+
+        // The header is only written once.
+        exporter.write_page(vec![tx_item(7)]).unwrap();
+        let content = fs::read_to_string(dir.join("txs.csv")).unwrap();
+        assert_eq!(content.lines().count(), 4);
+        assert!(content.lines().skip(1).all(|line| !line.starts_with("tx_num")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(super::csv_field("abc"), "abc");
+        assert_eq!(super::csv_field(""), "");
+        assert_eq!(super::csv_field("a,b"), "\"a,b\"");
+        assert_eq!(super::csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(super::csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(super::csv_field("cr\r"), "\"cr\r\"");
+    }
+}
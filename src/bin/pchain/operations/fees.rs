@@ -0,0 +1,206 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::str::FromStr;
+
+use futures::StreamExt;
+
+use crate::{display_types::{FeeSuggestion, FeeSuggestions}, operations::fetch, setup::{self, ConfigField}, stats, time_parser};
+
+// Fees module recommends the gas price and tip of new Transactions. The recommendation is based on the
+// gas prices and tips of Transactions in recent Blocks: slow, standard and fast pay the 25th, 50th and
+// 90th percentiles respectively. When the mempool is larger than its average over the last hour,
+// standard and fast tips are raised in proportion.
+
+// Number of latest Blocks sampled by default.
+pub const DEFAULT_SAMPLE_BLOCKS: u64 = 50;
+// Gas price suggested when the sampled Blocks contain no Transactions.
+const MIN_GAS_PRICE: u64 = 1;
+// Largest factor by which a congested mempool raises the suggested tips.
+const MAX_MEMPOOL_PRESSURE: f64 = 3.0;
+// Length (in seconds) of the mempool size history, and the window size its average is computed over.
+const MEMPOOL_HISTORY_SECS: u64 = 3600;
+const MEMPOOL_WINDOW_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Copy)]
+pub enum FeeLevel {
+    Slow,
+    Standard,
+    Fast,
+}
+
+impl FromStr for FeeLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "slow" => Ok(FeeLevel::Slow),
+            "standard" => Ok(FeeLevel::Standard),
+            "fast" => Ok(FeeLevel::Fast),
+            _ => Err(format!("unknown fee level `{}`. Expected one of slow, standard, fast", s)),
+        }
+    }
+}
+
+// Analyze suggest fees prints the recommended gas price and tip of each fee level.
+pub async fn analyze_suggest_fees(sample_blocks: u64, json: bool) {
+    let suggestions = fetch::or_exit(suggest_fees(sample_blocks).await);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&suggestions).unwrap());
+        return;
+    }
+
+    println!("Sampled {} Transactions in Blocks {} to {}", suggestions.sampled_transactions, suggestions.from_block, suggestions.to_block);
+    match (suggestions.mempool_size, suggestions.mean_mempool_size) {
+        (Some(size), Some(mean)) => println!("Mempool size: {} (average {:.0} over the last hour)", size, mean),
+        (Some(size), None) => println!("Mempool size: {}", size),
+        _ => {},
+    }
+    println!();
+    println!("{:<10} {:>12} {:>12}", "Level", "Gas Price", "Tip");
+    for (name, suggestion) in [("slow", suggestions.slow), ("standard", suggestions.standard), ("fast", suggestions.fast)] {
+        println!("{:<10} {:>12} {:>12}", name, suggestion.gas_price, suggestion.tip);
+    }
+}
+
+// Fees for level returns the recommended gas price and tip for `level`, for `submit --fee-level`.
+pub async fn fees_for_level(level: FeeLevel) -> FeeSuggestion {
+    let suggestions = fetch::or_exit(suggest_fees(DEFAULT_SAMPLE_BLOCKS).await);
+    match level {
+        FeeLevel::Slow => suggestions.slow,
+        FeeLevel::Standard => suggestions.standard,
+        FeeLevel::Fast => suggestions.fast,
+    }
+}
+
+// Resolve fees returns the tip and gas price of a Transaction. Values given explicitly take precedence
+// over the ones suggested for `fee_level`. One of them is always given, as enforced by the arguments.
+pub async fn resolve_fees(tip: Option<u64>, gas_price: Option<u64>, fee_level: Option<FeeLevel>) -> (u64, u64) {
+    match (tip, gas_price, fee_level) {
+        (Some(tip), Some(gas_price), _) => (tip, gas_price),
+        (tip, gas_price, Some(level)) => {
+            let suggestion = fees_for_level(level).await;
+            let (tip, gas_price) = (tip.unwrap_or(suggestion.tip), gas_price.unwrap_or(suggestion.gas_price));
+            println!("Using {:?} fees: gas price {}, tip {}", level, gas_price, tip);
+            (tip, gas_price)
+        },
+        _ => {
+            println!("Error: Specify either both 'tip' and 'gas-price', or 'fee-level'.");
+            std::process::exit(1);
+        }
+    }
+}
+
+pub(crate) async fn suggest_fees(sample_blocks: u64) -> Result<FeeSuggestions, String> {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+
+    let to = fetch::latest_block_number(&client, &rich_api_url).await?;
+    let from = to.saturating_sub(sample_blocks.max(1) - 1);
+    let mut gas_prices: Vec<f64> = vec![];
+    let mut tips: Vec<f64> = vec![];
+    let mut pages = Box::pin(fetch::block_pages(&client, &rich_api_url, from, to, fetch::MAX_PAGE_SIZE, 4));
+    while let Some((_, page)) = pages.next().await {
+        for block in page? {
+            gas_prices.extend(block.transactions.iter().map(|tx| tx.gas_price as f64));
+            tips.extend(block.transactions.iter().map(|tx| tx.tip as f64));
+        }
+    }
+
+    // The mempool only refines the recommendation, so it is skipped if it cannot be queried.
+    let mempool_size = match fetch::mempool_size(&client, &setup::read_config(ConfigField::TargetUrl)).await {
+        Ok(size) => Some(size),
+        Err(e) => {
+            eprintln!("Warning: Cannot query mempool size. {}", e);
+            None
+        }
+    };
+    let now = time_parser::now();
+    let mean_mempool_size = match fetch::analytics_series(&client, &setup::read_config(ConfigField::AnalyticsApiUrl), "mempool_size",
+        now.saturating_sub(MEMPOOL_HISTORY_SECS), now, MEMPOOL_WINDOW_SECS, MEMPOOL_WINDOW_SECS).await {
+        Ok(series) if !series.is_empty() => Some(series.iter().sum::<u64>() as f64 / series.len() as f64),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Warning: Cannot query mempool size history. {}", e);
+            None
+        }
+    };
+    let mempool_pressure = match (mempool_size, mean_mempool_size) {
+        (Some(size), Some(mean)) if mean > 0.0 => (size as f64 / mean).clamp(1.0, MAX_MEMPOOL_PRESSURE),
+        _ => 1.0,
+    };
+
+    let [slow, standard, fast] = recommend(&gas_prices, &tips, mempool_pressure);
+    Ok(FeeSuggestions {
+        from_block: from,
+        to_block: to,
+        sampled_transactions: gas_prices.len() as u64,
+        mempool_size,
+        mean_mempool_size,
+        mempool_pressure,
+        slow,
+        standard,
+        fast,
+    })
+}
+
+// Recommend returns the slow, standard and fast fees. Each level pays at least as much as the level
+// before it, and fast always tips more than standard.
+fn recommend(gas_prices: &[f64], tips: &[f64], mempool_pressure: f64) -> [FeeSuggestion; 3] {
+    let sorted = |values: &[f64]| {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        sorted
+    };
+    let (gas_prices, tips) = (sorted(gas_prices), sorted(tips));
+    let gas_price = |p: f64| if gas_prices.is_empty() { MIN_GAS_PRICE } else { stats::percentile(&gas_prices, p).ceil() as u64 };
+    let tip = |p: f64, pressure: f64| if tips.is_empty() { 0 } else { (stats::percentile(&tips, p) * pressure).ceil() as u64 };
+
+    let slow = FeeSuggestion { gas_price: gas_price(25.0), tip: tip(25.0, 1.0) };
+    let standard = FeeSuggestion {
+        gas_price: gas_price(50.0).max(slow.gas_price),
+        tip: tip(50.0, mempool_pressure).max(slow.tip),
+    };
+    let fast = FeeSuggestion {
+        gas_price: gas_price(90.0).max(standard.gas_price),
+        tip: tip(90.0, mempool_pressure).max(standard.tip + 1),
+    };
+    [slow, standard, fast]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::display_types::FeeSuggestion;
+
+    #[test]
+    fn test_recommend() {
+        let gas_prices: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        let tips: Vec<f64> = vec![0.0, 0.0, 1.0, 2.0, 2.0, 3.0, 5.0, 5.0, 8.0, 10.0];
+        let [slow, standard, fast] = super::recommend(&gas_prices, &tips, 1.0);
+        assert_eq!(slow, FeeSuggestion { gas_price: 3, tip: 1 });
+        assert_eq!(standard, FeeSuggestion { gas_price: 5, tip: 2 });
+        assert_eq!(fast, FeeSuggestion { gas_price: 9, tip: 8 });
+
+        let [_, standard, fast] = super::recommend(&gas_prices, &tips, 2.0);
+        assert_eq!(standard.tip, 4);
+        assert_eq!(fast.tip, 16);
+
+        let [slow, standard, fast] = super::recommend(&[], &[], 1.0);
+        assert_eq!(slow, FeeSuggestion { gas_price: super::MIN_GAS_PRICE, tip: 0 });
+        assert_eq!(standard, FeeSuggestion { gas_price: super::MIN_GAS_PRICE, tip: 0 });
+        assert_eq!(fast, FeeSuggestion { gas_price: super::MIN_GAS_PRICE, tip: 1 });
+    }
+}
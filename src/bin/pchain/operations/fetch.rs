@@ -0,0 +1,288 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::convert::TryInto;
+
+use futures::{stream, Future, Stream, StreamExt};
+use protocol_types::Deserializable;
+
+use crate::{display_types::{self, BlockSummary, BytesEncoding, EncodedFrom, TxnSummary}, operations::query::http_formatted};
+
+// Fetch module retrieves data from the REST APIs and returns it decoded instead of printing it.
+// It is used by commands which post-process the responses, e.g. following or scanning blocks.
+// Errors are returned rather than exiting, so that callers can decide to retry.
+
+// Or exit prints the error of a failed fetch and exits, for commands which cannot continue without the data.
+pub(crate) fn or_exit<T>(result: Result<T, String>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Get bytes sends a GET request and returns the response body, or a description of the failure.
+pub(crate) async fn get_bytes(client: &reqwest::Client, route: String) -> Result<Vec<u8>, String> {
+    let resp = match client.get(route).send().await {
+        Ok(some_resp) => some_resp,
+        Err(e) => return Err(format!("Server connection error. Detail: {}", e)),
+    };
+    read_response(resp).await
+}
+
+// Get bytes if found is the same as get bytes, except that it returns None if the server answers 404 Not Found.
+pub(crate) async fn get_bytes_if_found(client: &reqwest::Client, route: String) -> Result<Option<Vec<u8>>, String> {
+    let resp = match client.get(route).send().await {
+        Ok(some_resp) => some_resp,
+        Err(e) => return Err(format!("Server connection error. Detail: {}", e)),
+    };
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    read_response(resp).await.map(Some)
+}
+
+async fn read_response(resp: reqwest::Response) -> Result<Vec<u8>, String> {
+    let status = resp.status();
+    if status.is_success() {
+        match resp.bytes().await {
+            Ok(bytes) => Ok(bytes.to_vec()),
+            Err(e) => Err(format!("Fail to read response. Detail: {}", e)),
+        }
+    } else {
+        let resp_detail = resp.text().await.unwrap_or_default();
+        Err(format!("Query Error. Status: {:?}. Detail: {}", status, http_formatted(resp_detail)))
+    }
+}
+
+// Blocks queries the Rich API `/blocks` endpoint. `selector` is one of the selecting query arguments,
+// e.g. `num=10` or `latest=true`.
+pub(crate) async fn blocks(client: &reqwest::Client, rich_api_url: &str, selector: &str, window: u64) -> Result<Vec<protocol_types::Block>, String> {
+    let route = format!("{}/blocks?{}&window={}&header_only=false", rich_api_url, selector, window);
+    let bytes = get_bytes(client, route).await?;
+    Vec::<protocol_types::Block>::deserialize(&bytes).map_err(|e| format!("Fail to decode blocks. {:?}", e))
+}
+
+pub(crate) async fn block_headers(client: &reqwest::Client, rich_api_url: &str, selector: &str, window: u64) -> Result<Vec<protocol_types::BlockHeader>, String> {
+    let route = format!("{}/blocks?{}&window={}&header_only=true", rich_api_url, selector, window);
+    let bytes = get_bytes(client, route).await?;
+    Vec::<protocol_types::BlockHeader>::deserialize(&bytes).map_err(|e| format!("Fail to decode block headers. {:?}", e))
+}
+
+pub(crate) async fn block_summaries(client: &reqwest::Client, rich_api_url: &str, selector: &str, window: u64, header_only: bool) -> Result<Vec<BlockSummary>, String> {
+    let route = format!("{}/blocks?{}&window={}&header_only={}&summary_only=true", rich_api_url, selector, window, header_only);
+    let bytes = get_bytes(client, route).await?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Fail to decode block summaries. {}", e))
+}
+
+// Transactions queries the Rich API `/transactions` endpoint, returning the transaction number of each Transaction along with it.
+pub(crate) async fn transactions(client: &reqwest::Client, rich_api_url: &str, selector: &str, window: u64) -> Result<Vec<(u64, protocol_types::Transaction, protocol_types::Receipt)>, String> {
+    let route = format!("{}/transactions?{}&window={}", rich_api_url, selector, window);
+    let bytes = get_bytes(client, route).await?;
+    Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)>::deserialize(&bytes).map_err(|e| format!("Fail to decode transactions. {:?}", e))
+}
+
+// Transactions if found is the same as transactions, except that it returns None if the Rich API does not know
+// the selected Transaction, e.g. a `tx_hash` which is not included on chain.
+pub(crate) async fn transactions_if_found(client: &reqwest::Client, rich_api_url: &str, selector: &str, window: u64) -> Result<Option<Vec<(u64, protocol_types::Transaction, protocol_types::Receipt)>>, String> {
+    let route = format!("{}/transactions?{}&window={}", rich_api_url, selector, window);
+    let bytes = match get_bytes_if_found(client, route).await? {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+    Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)>::deserialize(&bytes).map(Some).map_err(|e| format!("Fail to decode transactions. {:?}", e))
+}
+
+// Transaction included tells whether the Transaction with the Base64 encoded `tx_hash` is included on chain.
+pub(crate) async fn transaction_included(client: &reqwest::Client, rich_api_url: &str, tx_hash: &str) -> Result<bool, String> {
+    let txs = transactions_if_found(client, rich_api_url, &format!("tx_hash={}", tx_hash), 1).await?.unwrap_or_default();
+    Ok(txs.iter().any(|(_, tx, _)| protocol_types::Base64URL::encode(tx.hash).to_string() == tx_hash))
+}
+
+pub(crate) async fn transaction_summaries(client: &reqwest::Client, rich_api_url: &str, selector: &str, window: u64) -> Result<Vec<TxnSummary>, String> {
+    let route = format!("{}/transactions?{}&window={}&summary_only=true", rich_api_url, selector, window);
+    let bytes = get_bytes(client, route).await?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Fail to decode transaction summaries. {}", e))
+}
+
+// Latest block number returns the height of the newest block known to the Rich API.
+pub(crate) async fn latest_block_number(client: &reqwest::Client, rich_api_url: &str) -> Result<u64, String> {
+    match block_headers(client, rich_api_url, "latest=true", 1).await?.iter().map(|header| header.block_number).max() {
+        Some(block_number) => Ok(block_number),
+        None => Err("Rich API returned no latest block".to_string()),
+    }
+}
+
+// Mempool size queries the Standard API `/mempoolsize` endpoint.
+pub(crate) async fn mempool_size(client: &reqwest::Client, target_url: &str) -> Result<u64, String> {
+    let bytes = get_bytes(client, format!("{}/mempoolsize", target_url)).await?;
+    match bytes.as_slice().try_into() {
+        Ok(buf) => Ok(u64::from_le_bytes(buf)),
+        Err(_) => Err(format!("Fail to decode mempool size from {} bytes", bytes.len())),
+    }
+}
+
+// Analytics series queries an analytics API moving-window series, e.g. `category` "mempool_size" or "gas_per_block".
+pub(crate) async fn analytics_series(client: &reqwest::Client, analytics_api_url: &str, category: &str, from_time: u64, to_time: u64, window_size: u64, step_size: u64) -> Result<Vec<u64>, String> {
+    let route = format!("{}/{}?from_time={}&to_time={}&window_size={}&step_size={}", analytics_api_url, category, from_time, to_time, window_size, step_size);
+    let bytes = get_bytes(client, route).await?;
+    if bytes.len() % 8 != 0 {
+        return Err(format!("Fail to decode {} series from {} bytes", category, bytes.len()));
+    }
+    Ok(bytes.chunks(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap())).collect())
+}
+
+// Block mode selects how much of each Block is retrieved from the Rich API.
+pub enum BlockMode {
+    Blocks,
+    BlockHeaders,
+    BlockSummary,
+}
+
+pub(crate) enum FetchedBlock {
+    Block(protocol_types::Block),
+    BlockHeader(protocol_types::BlockHeader),
+    BlockSummary(BlockSummary),
+}
+
+impl FetchedBlock {
+    pub(crate) fn block_number(&self) -> u64 {
+        match self {
+            FetchedBlock::Block(block) => block.header.block_number,
+            FetchedBlock::BlockHeader(header) => header.block_number,
+            FetchedBlock::BlockSummary(summary) => summary.height,
+        }
+    }
+
+    // Print the Block either in the Debug format or as one line of JSON, with free-form bytes in `encoding`.
+    pub(crate) fn print(self, json: bool, encoding: BytesEncoding) {
+        match self {
+            FetchedBlock::Block(block) => print_item(&display_types::Block::encoded_from(block, encoding), json),
+            FetchedBlock::BlockHeader(header) => print_item(&display_types::BlockHeader::from(header), json),
+            FetchedBlock::BlockSummary(summary) => print_item(&summary, json),
+        }
+    }
+}
+
+// Print item prints a display type either in the Debug format or as one line of JSON.
+pub(crate) fn print_item<T: std::fmt::Debug + serde::Serialize>(item: &T, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string(item).unwrap());
+    } else {
+        println!("{:#?}", item);
+    }
+}
+
+// Blocks in mode returns up to `window` Blocks starting from block number `from`.
+pub(crate) async fn blocks_in_mode(client: &reqwest::Client, rich_api_url: &str, mode: &BlockMode, from: u64, window: u64) -> Result<Vec<FetchedBlock>, String> {
+    let selector = format!("num={}", from);
+    Ok(match mode {
+        BlockMode::Blocks => blocks(client, rich_api_url, &selector, window).await?
+            .into_iter().map(FetchedBlock::Block).collect(),
+        BlockMode::BlockHeaders => block_headers(client, rich_api_url, &selector, window).await?
+            .into_iter().map(FetchedBlock::BlockHeader).collect(),
+        BlockMode::BlockSummary => block_summaries(client, rich_api_url, &selector, window, false).await?
+            .into_iter().map(FetchedBlock::BlockSummary).collect(),
+    })
+}
+
+// Latest tx number returns the transaction number of the newest Transaction known to the Rich API.
+pub(crate) async fn latest_tx_number(client: &reqwest::Client, rich_api_url: &str) -> Result<u64, String> {
+    match transactions(client, rich_api_url, "latest=true", 1).await?.iter().map(|(tx_num, _, _)| *tx_num).max() {
+        Some(tx_num) => Ok(tx_num),
+        None => Err("Rich API returned no latest transaction".to_string()),
+    }
+}
+
+// Largest window requested from the Rich API in a single page.
+pub(crate) const MAX_PAGE_SIZE: u64 = 100;
+
+// Number of Blocks requested at once by commands which scan Blocks one page at a time.
+pub(crate) const SCAN_PAGE_SIZE: u64 = 50;
+
+// Pages splits the inclusive range [from, to] into (start, window) pairs of at most `page_size` items. The pages
+// are made lazily, so that ranges of any size can be paged.
+pub(crate) fn pages(from: u64, to: u64, page_size: u64) -> impl Iterator<Item = (u64, u64)> {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let page = move |start: u64| (start, (to - start).min(page_size - 1) + 1);
+    let first = if from <= to { Some(page(from)) } else { None };
+    std::iter::successors(first, move |(start, window)| match start.checked_add(*window) {
+        Some(next) if next <= to => Some(page(next)),
+        _ => None,
+    })
+}
+
+// Paged fetches the pages of [from, to] with up to `concurrency` requests in flight. Pages are
+// yielded in order as soon as they (and all pages before them) arrive, so that callers can stream
+// the results instead of holding the whole range in memory. Each page is yielded with its (start, window).
+pub(crate) fn paged<T, F, Fut>(from: u64, to: u64, page_size: u64, concurrency: usize, fetch_page: F) -> impl Stream<Item = ((u64, u64), Result<Vec<T>, String>)>
+where
+    F: Fn(u64, u64) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, String>>,
+{
+    stream::iter(pages(from, to, page_size))
+        .map(move |(start, window)| {
+            let page = fetch_page(start, window);
+            async move { ((start, window), page.await) }
+        })
+        .buffered(concurrency.max(1))
+}
+
+// In page tells whether `number` is one of the `window` numbers starting from `start`.
+pub(crate) fn in_page((start, window): (u64, u64), number: u64) -> bool {
+    number >= start && number - start < window
+}
+
+// Block pages fetches the Blocks numbered `from` to `to` (inclusive) with `paged`. Blocks outside of
+// the page they were requested for are dropped, and the rest are sorted by block number.
+pub(crate) fn block_pages<'a>(client: &'a reqwest::Client, rich_api_url: &'a str, from: u64, to: u64, page_size: u64, concurrency: usize) -> impl Stream<Item = ((u64, u64), Result<Vec<protocol_types::Block>, String>)> + 'a {
+    paged(from, to, page_size, concurrency, move |start, window| async move {
+        blocks(client, rich_api_url, &format!("num={}", start), window).await
+    }).map(|(page, blocks)| {
+        let blocks = blocks.map(|mut blocks| {
+            blocks.retain(|block| in_page(page, block.header.block_number));
+            blocks.sort_by_key(|block| block.header.block_number);
+            blocks
+        });
+        (page, blocks)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_pages() {
+        let pages = |from, to, page_size| super::pages(from, to, page_size).collect::<Vec<(u64, u64)>>();
+        assert_eq!(pages(0, 9, 4), vec![(0, 4), (4, 4), (8, 2)]);
+        assert_eq!(pages(5, 5, 50), vec![(5, 1)]);
+        assert_eq!(pages(6, 5, 50), vec![]);
+        assert_eq!(pages(0, 250, 1000), vec![(0, 100), (100, 100), (200, 51)]);
+        assert_eq!(pages(u64::MAX - 1, u64::MAX, 0), vec![(u64::MAX - 1, 1), (u64::MAX, 1)]);
+        assert_eq!(super::pages(0, u64::MAX, 50).take(2).collect::<Vec<(u64, u64)>>(), vec![(0, 50), (50, 50)]);
+    }
+
+    #[test]
+    fn test_in_page() {
+        assert!(super::in_page((10, 5), 10));
+        assert!(super::in_page((10, 5), 14));
+        assert!(!super::in_page((10, 5), 9));
+        assert!(!super::in_page((10, 5), 15));
+        assert!(super::in_page((u64::MAX, 1), u64::MAX));
+        assert!(!super::in_page((u64::MAX - 1, 1), u64::MAX));
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Fixtures module builds the protocol types used in tests of the operations modules. Each fixture is filled
+// with zeros, and tests build their variants from it with struct update syntax.
+
+pub(crate) fn transaction() -> protocol_types::Transaction {
+    protocol_types::Transaction {
+        from_address: [0u8; 32],
+        to_address: [0u8; 32],
+        value: 0,
+        tip: 0,
+        gas_limit: 0,
+        gas_price: 0,
+        data: vec![],
+        n_txs_on_chain_from_address: 0,
+        hash: [0u8; 32],
+        signature: [0u8; 64],
+    }
+}
+
+pub(crate) fn receipt() -> protocol_types::Receipt {
+    protocol_types::Receipt {
+        status_code: protocol_types::ReceiptStatusCode::Success,
+        gas_consumed: 0,
+        return_value: vec![],
+        events: vec![],
+    }
+}
+
+pub(crate) fn block_header(block_number: u64) -> protocol_types::BlockHeader {
+    protocol_types::BlockHeader {
+        blockchain_id: 1,
+        block_version_number: 1,
+        block_number,
+        timestamp: 0,
+        prev_block_hash: [0u8; 32],
+        this_block_hash: [0u8; 32],
+        txs_hash: [0u8; 32],
+        state_hash: [0u8; 32],
+        receipts_hash: [0u8; 32],
+        proposer_public_key: [0u8; 32],
+        signature: [0u8; 64],
+    }
+}
+
+// Block returns a Block with `transactions` at `block_number`, and a Receipt for each of them.
+pub(crate) fn block(block_number: u64, transactions: Vec<protocol_types::Transaction>) -> protocol_types::Block {
+    protocol_types::Block {
+        header: block_header(block_number),
+        receipts: transactions.iter().map(|_| receipt()).collect(),
+        transactions,
+    }
+}
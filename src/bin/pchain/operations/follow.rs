@@ -0,0 +1,109 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::time::Duration;
+
+use crate::{display_types::BytesEncoding, operations::fetch::{self, BlockMode}, setup::{self, ConfigField}};
+
+// Follow module tails the chain. It polls the latest block from the Rich API, remembers the last
+// block it printed, and backfills every block in between so that no block is skipped.
+
+// Largest number of blocks requested at once when catching up.
+const BACKFILL_WINDOW: u64 = 50;
+// Waiting time after a failed poll doubles up to this many seconds.
+const MAX_BACKOFF_SECS: u64 = 60;
+
+// Follow blocks prints every new block as it is produced, until the process is interrupted.
+// Transient failures are retried with exponential backoff.
+pub async fn follow_blocks(mode: BlockMode, json: bool, interval_secs: u64, encoding: BytesEncoding) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+
+    let mut last_seen: Option<u64> = None;
+    let mut backoff_secs = interval_secs.max(1);
+    loop {
+        match poll(&client, &rich_api_url, &mode, json, encoding, &mut last_seen).await {
+            Ok(_) => {
+                backoff_secs = interval_secs.max(1);
+                tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+            },
+            Err(e) => {
+                eprintln!("Warning: {}. Retrying in {} seconds.", e, backoff_secs);
+                tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
+                backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
+            }
+        }
+    }
+}
+
+// Poll prints the blocks produced since `last_seen`. On the first poll only the latest block is printed.
+async fn poll(client: &reqwest::Client, rich_api_url: &str, mode: &BlockMode, json: bool, encoding: BytesEncoding, last_seen: &mut Option<u64>) -> Result<(), String> {
+    let latest = fetch::latest_block_number(client, rich_api_url).await?;
+    let mut next = match last_seen {
+        Some(seen) => *seen + 1,
+        None => latest,
+    };
+
+    while next <= latest {
+        let window = (latest - next + 1).min(BACKFILL_WINDOW);
+        let mut blocks = fetch::blocks_in_mode(client, rich_api_url, mode, next, window).await?;
+        blocks.sort_by_key(|block| block.block_number());
+
+        let blocks = contiguous(blocks, |block| block.block_number(), next, latest);
+        if blocks.is_empty() {
+            return Err(format!("Rich API returned no block with block number {}", next));
+        }
+        // Blocks after a gap in the window are requested again from the first missing one.
+        for block in blocks {
+            next = block.block_number() + 1;
+            *last_seen = Some(block.block_number());
+            block.print(json, encoding);
+        }
+    }
+    Ok(())
+}
+
+// Contiguous returns the items of `items`, sorted by block number, which follow `next` without a gap, up to `latest`.
+// Items before `next` were printed already and are skipped.
+fn contiguous<T>(items: Vec<T>, block_number: impl Fn(&T) -> u64, next: u64, latest: u64) -> Vec<T> {
+    let mut expected = next;
+    let mut contiguous = vec![];
+    for item in items {
+        let number = block_number(&item);
+        if number < expected {
+            continue;
+        }
+        if number != expected || number > latest {
+            break;
+        }
+        contiguous.push(item);
+        expected += 1;
+    }
+    contiguous
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_contiguous() {
+        let contiguous = |numbers: Vec<u64>, next, latest| super::contiguous(numbers, |n| *n, next, latest);
+        assert_eq!(contiguous(vec![5, 6, 7], 5, 10), vec![5, 6, 7]);
+        assert_eq!(contiguous(vec![5, 6, 8, 9], 5, 10), vec![5, 6]);
+        assert_eq!(contiguous(vec![6, 7], 5, 10), Vec::<u64>::new());
+        assert_eq!(contiguous(vec![3, 4, 5, 5, 6], 5, 10), vec![5, 6]);
+        assert_eq!(contiguous(vec![5, 6, 7], 5, 6), vec![5, 6]);
+    }
+}
@@ -0,0 +1,182 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::{convert::TryInto, str::FromStr};
+
+use futures::StreamExt;
+
+use crate::{display_types::AccountTxnSummary, operations::fetch, setup::{self, ConfigField}, Base64Address};
+
+// History module lists the Transactions sent from or to an Account. Blocks are scanned
+// page by page through the Rich API, and a cursor is printed so that the next page can be requested.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    In,
+    Out,
+    Both,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "in" => Ok(Direction::In),
+            "out" => Ok(Direction::Out),
+            "both" => Ok(Direction::Both),
+            _ => Err(format!("unknown direction `{}`. Expected one of in, out, both", s)),
+        }
+    }
+}
+
+pub async fn account_history(address: Base64Address, from_block: u64, direction: Direction, limit: u64, json: bool) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+
+    let account: protocol_types::PublicAddress = match protocol_types::Base64URL::decode(&address).map(|a| a.try_into()) {
+        Ok(Ok(account)) => account,
+        _ => {
+            println!("Error: 'address' must be a Base64 encoded address of 32 bytes.");
+            std::process::exit(1);
+        }
+    };
+
+    let latest = fetch::or_exit(fetch::latest_block_number(&client, &rich_api_url).await);
+    let mut pages = Box::pin(fetch::block_pages(&client, &rich_api_url, from_block, latest, fetch::SCAN_PAGE_SIZE, 1));
+    let mut rows = vec![];
+    let mut next_block = from_block;
+    while (rows.len() as u64) < limit {
+        let (page, blocks) = match pages.next().await {
+            Some((page, blocks)) => (page, fetch::or_exit(blocks)),
+            None => break,
+        };
+        next_block = scan_page(blocks, page, &account, direction, limit, &mut rows);
+    }
+
+    print_rows(&rows, json);
+    print_cursor(Some(next_block), next_block <= latest, json);
+}
+
+// Scan page appends the Transactions of the Account in the sorted Blocks of `page` (start, window) to `rows`, and
+// returns the block number to continue from. This is past the end of the page unless `limit` was reached. It then
+// points at the Block after the last one scanned, as a page of results always ends at a Block boundary.
+fn scan_page(blocks: Vec<protocol_types::Block>, (start, window): (u64, u64), account: &protocol_types::PublicAddress, direction: Direction, limit: u64, rows: &mut Vec<AccountTxnSummary>) -> u64 {
+    for block in blocks {
+        if (rows.len() as u64) >= limit {
+            return block.header.block_number;
+        }
+        for (tx, receipt) in block.transactions.iter().zip(block.receipts.iter()) {
+            if let Some(row) = account_txn(block.header.block_number, tx, receipt, account, direction) {
+                rows.push(row);
+            }
+        }
+    }
+    start.saturating_add(window)
+}
+
+fn account_txn(block_number: u64, tx: &protocol_types::Transaction, receipt: &protocol_types::Receipt, account: &protocol_types::PublicAddress, direction: Direction) -> Option<AccountTxnSummary> {
+    let is_out = &tx.from_address == account;
+    let is_in = &tx.to_address == account;
+    let row_direction = match (is_in, is_out) {
+        (true, true) => "self",
+        (true, false) if direction != Direction::Out => "in",
+        (false, true) if direction != Direction::In => "out",
+        _ => return None,
+    };
+    Some(AccountTxnSummary {
+        block_number,
+        direction: row_direction.to_string(),
+        hash: protocol_types::Base64URL::encode(tx.hash).to_string(),
+        from_address: protocol_types::Base64URL::encode(tx.from_address).to_string(),
+        to_address: protocol_types::Base64URL::encode(tx.to_address).to_string(),
+        value: tx.value,
+        status_code: receipt.status_code as u8,
+    })
+}
+
+fn print_rows(rows: &[AccountTxnSummary], json: bool) {
+    for row in rows {
+        fetch::print_item(row, json);
+    }
+}
+
+fn print_cursor(next_block: Option<u64>, has_more: bool, json: bool) {
+    // The cursor goes to stderr in JSON mode, so that stdout stays one transaction per line.
+    match next_block {
+        Some(next_block) if has_more => {
+            let cursor = format!("More transactions may follow. Next page: --from-block {}", next_block);
+            if json { eprintln!("{}", cursor) } else { println!("{}", cursor) }
+        },
+        _ => {
+            if !json { println!("No more transactions.") }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::operations::fixtures;
+
+    use super::Direction;
+
+    const ACCOUNT: [u8; 32] = [1u8; 32];
+
+    fn block(block_number: u64, txs: Vec<([u8; 32], [u8; 32])>) -> protocol_types::Block {
+        fixtures::block(block_number, txs.into_iter().map(|(from_address, to_address)| protocol_types::Transaction {
+            from_address,
+            to_address,
+            value: 1,
+            hash: [block_number as u8; 32],
+            ..fixtures::transaction()
+        }).collect())
+    }
+
+    #[test]
+    fn test_scan_page_direction() {
+        let directions = |direction| {
+            let blocks = vec![block(0, vec![(ACCOUNT, [2u8; 32]), ([2u8; 32], ACCOUNT), ([2u8; 32], [3u8; 32]), (ACCOUNT, ACCOUNT)])];
+            let mut rows = vec![];
+            super::scan_page(blocks, (0, 1), &ACCOUNT, direction, 100, &mut rows);
+            rows.into_iter().map(|row| row.direction).collect::<Vec<String>>()
+        };
+        assert_eq!(directions(Direction::Both), vec!["out", "in", "self"]);
+        assert_eq!(directions(Direction::In), vec!["in", "self"]);
+        assert_eq!(directions(Direction::Out), vec!["out", "self"]);
+    }
+
+    #[test]
+    fn test_scan_page_cursor() {
+        // The scan moves past a page even if the Rich API returned none of its Blocks.
+        let mut rows = vec![];
+        let next_block = super::scan_page(vec![], (10, 5), &ACCOUNT, Direction::Both, 100, &mut rows);
+        assert_eq!(next_block, 15);
+        assert!(rows.is_empty());
+
+        // A page of results ends after the Block in which the limit was reached.
+        let blocks = vec![
+            block(10, vec![(ACCOUNT, [2u8; 32]), (ACCOUNT, [2u8; 32])]),
+            block(11, vec![(ACCOUNT, [2u8; 32])]),
+            block(12, vec![(ACCOUNT, [2u8; 32])]),
+        ];
+        let next_block = super::scan_page(blocks, (10, 5), &ACCOUNT, Direction::Both, 1, &mut rows);
+        assert_eq!(next_block, 11);
+        assert_eq!(rows.len(), 2);
+
+        let next_block = super::scan_page(vec![], (u64::MAX, 1), &ACCOUNT, Direction::Both, 1, &mut vec![]);
+        assert_eq!(next_block, u64::MAX);
+    }
+}
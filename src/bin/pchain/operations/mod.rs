@@ -25,3 +25,23 @@ pub use submit::*;
 
 pub mod crypto;
 pub use crypto::*;
+
+pub mod fetch;
+
+pub mod follow;
+
+pub mod events;
+
+pub mod history;
+
+pub mod tx_status;
+pub mod range;
+pub mod export;
+pub mod verify;
+pub mod analyze;
+pub mod fees;
+pub mod series;
+pub mod batch;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
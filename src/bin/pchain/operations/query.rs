@@ -16,8 +16,8 @@
  */
 use std::{convert::TryInto, fs};
 
-use crate::{display_types::*, setup::{ConfigField, self}, args_parser};
-use protocol_types::Deserializable;
+use crate::{display_types::*, setup::{ConfigField, self}, args_parser, input, prt_parser};
+use protocol_types::{Deserializable, Serializable};
 
 // Query module handles all query request.
 // It formats the query string and assemble the target address for query.
@@ -39,23 +39,28 @@ pub enum QueryOption{
     ContractCode,
     ContractMetadata,
     WorldState,
+    WorldStateProof,
     TxProof,
     ReceiptProof,
     Mempoolsize,
-
-    GasPerBlock,
-    MempoolSize,
     None,
 }
 
 pub(crate) struct Api {
     standard: String,
     rich: String,
-    analytics: String,
 }
 
 // Query funtion map each different kind of queries to their corresponding endpoint.
 pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
+    query_and_save(query_option, query_inputs, None, BytesEncoding::Auto).await
+}
+
+// Query and save is the same as query, but also saves the raw Borsh bytes of the returned Blocks, Transactions
+// and proofs as Prt files at `save`, which can be displayed later with `parse prt`. Free-form bytes of Blocks
+// and Transactions are displayed in `encoding`.
+pub async fn query_and_save(query_option: QueryOption, query_inputs: Vec<String>, save: Option<String>, encoding: BytesEncoding){
+    let save = save.as_deref();
     // Retrive target host address to query upon
     let client = reqwest::Client::new();
     //let route_address = setup::read_config(ConfigField::RichApiUrl);
@@ -63,7 +68,6 @@ pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
     let api = Api {
         standard: setup::read_config(ConfigField::TargetUrl),
         rich: setup::read_config(ConfigField::RichApiUrl),
-        analytics: setup::read_config(ConfigField::AnalyticsApiUrl),
     };
 
     //let route_address = route_address_raw.as_str();// &route_address_raw[1..(route_address_raw.len()-1)];
@@ -104,10 +108,10 @@ pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
             } else if header_only == &"true".to_string() {
                 QueryReturnType::BlockHeaders 
             } else { 
-                QueryReturnType::Blocks
+                QueryReturnType::Blocks(encoding)
             };
 
-            query_helper(route, None, query_type, client).await;
+            query_helper(route, None, query_type, save, client).await;
         },
         QueryOption::TxsByTxHash | QueryOption::TxsByTxNum | QueryOption::TxsLatest => {
             let selector_argument = &query_inputs[0];
@@ -135,10 +139,10 @@ pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
             if summary_only == &"true".to_string() {
                 QueryReturnType::TransactionSummary
             } else {
-                QueryReturnType::Transactions
+                QueryReturnType::Transactions(encoding)
             };
 
-            query_helper(route, None, query_type, client).await;
+            query_helper(route, None, query_type, save, client).await;
         },
         /////////////////////////
         // Standard Rest APIs
@@ -147,28 +151,34 @@ pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
         QueryOption::Balance => {
             let address = &query_inputs[0];
             let route = format!("{}/account/{}/balance?proof=false", &api.standard, address);
-            query_helper(route, None, QueryReturnType::NumberU64, client).await;
+            query_helper(route, None, QueryReturnType::NumberU64, None, client).await;
         },
         QueryOption::Nonce => {
             let address = &query_inputs[0];
             let route = format!("{}/account/{}/nonce?proof=false", &api.standard, address);
-            query_helper(route, None, QueryReturnType::NumberU64, client).await;
+            query_helper(route, None, QueryReturnType::NumberU64, None, client).await;
         },
         QueryOption::ContractCode => {
             let address = &query_inputs[0];
             let route = format!("{}/account/{}/code?proof=false", &api.standard, address);
-            query_helper(route, None, QueryReturnType::Binary, client).await;
+            query_helper(route, None, QueryReturnType::Binary, None, client).await;
         },
         QueryOption::ContractMetadata => {
             let address = &query_inputs[0];
             let route = format!("{}/account/{}/metadata", &api.standard, address);
-            query_helper(route, None, QueryReturnType::Text, client).await;
+            query_helper(route, None, QueryReturnType::Text, None, client).await;
         }
         QueryOption::WorldState => {
             let address = &query_inputs[0];
             let key = &query_inputs[1];
             let route = format!("{}/account/{}/state?key={}&proof=false", &api.standard, address, key);
-            query_helper(route, None, QueryReturnType::Text, client).await;
+            query_helper(route, None, QueryReturnType::Text, None, client).await;
+        },
+        QueryOption::WorldStateProof => {
+            let address = &query_inputs[0];
+            let key = &query_inputs[1];
+            let route = format!("{}/account/{}/state?key={}&proof=true", &api.standard, address, key);
+            query_helper(route, None, QueryReturnType::proof("sprfs", save), save, client).await;
         },
         QueryOption::View => {
             let address = &query_inputs[0];
@@ -182,7 +192,7 @@ pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
             let expected_callback = &query_inputs[2];
 
             let route = format!("{}/account/{}/view", &api.standard, address);
-            let encoded_callback = query_helper(route, Some(calldata), QueryReturnType::Callback, client).await;
+            let encoded_callback = query_helper(route, Some(calldata), QueryReturnType::Callback, None, client).await;
             if expected_callback != &"".to_string() {
                 println!("Your callback value (parsed) {}", args_parser::from_callback(encoded_callback, expected_callback.clone()));
             }
@@ -191,72 +201,55 @@ pub async fn query(query_option: QueryOption, query_inputs: Vec<String>){
             let block_hash = &query_inputs[0];
             let tx_hash = &query_inputs[1];
             let route = format!("{}/proof/transaction/block/{}/transaction/{}", &api.standard, block_hash, tx_hash);
-            query_helper(route, None, QueryReturnType::Text, client).await;
+            query_helper(route, None, QueryReturnType::proof("mprf", save), save, client).await;
         },
         QueryOption::ReceiptProof => {
             let block_hash = &query_inputs[0];
             let tx_hash = &query_inputs[1];
 
             let route = format!("{}/proof/receipt/block/{}/transaction/{}", &api.standard, block_hash, tx_hash);
-            query_helper(route, None, QueryReturnType::Text, client).await;
+            query_helper(route, None, QueryReturnType::proof("mprf", save), save, client).await;
         },
         QueryOption::Mempoolsize => {
             let route = format!("{}/mempoolsize", &api.standard);
-            query_helper(route, None, QueryReturnType::NumberU64, client).await;
+            query_helper(route, None, QueryReturnType::NumberU64, None, client).await;
         }
 
-        /////////////////////////
-        // Analytics Rest APIs
-        /////////////////////////
-
-        QueryOption::GasPerBlock |  QueryOption::MempoolSize => {
-            let converted_inputs: Vec<u64> = query_inputs.iter().map(|q|{
-                match q.parse() {
-                    Ok(v) => v,
-                    Err(_) => {
-                        println!("Illegal input. It should be a number");
-                        std::process::exit(1); 
-                    }
-                }
-            }).collect();
-            let from_time:u64 = converted_inputs[0];
-            let to_time:u64 = converted_inputs[1];
-            let window_size:u64 = converted_inputs[2];
-            let step_size:u64 =  converted_inputs[3];
-
-            let category = match query_option {
-                QueryOption::GasPerBlock => "gas_per_block",
-                QueryOption::MempoolSize => "mempool_size",
-                _ => unreachable!()
-            };
-
-            let route = format!("{}/{}?from_time={}&to_time={}&window_size={}&step_size={}", &api.analytics, category, from_time, to_time, window_size, step_size);
-            query_helper(route, None, QueryReturnType::VectorU64, client).await;
-        },
         QueryOption::None => {println!("You should not reach here.")},
     }
 }
 
 enum QueryReturnType{
-    Blocks,
+    Blocks(BytesEncoding),
     BlockHeaders,
     BlockSummary,
-    Transactions,
+    Transactions(BytesEncoding),
     TransactionSummary,
-    VectorU64,
     Binary,
     Text,
+    // Base64URL encoded Borsh bytes of the protocol type with the given Prt data type extension.
+    Prt(&'static str),
     NumberU64,
     Callback,
 }
 
+impl QueryReturnType {
+    // Proof returns the type of a proof query, which is parsed as a Prt of data type `ext` only if it is saved.
+    fn proof(ext: &'static str, save: Option<&str>) -> Self {
+        match save {
+            Some(_) => QueryReturnType::Prt(ext),
+            None => QueryReturnType::Text,
+        }
+    }
+}
+
 // Query helper sned the request and helps to transalte the return result from restAPI endpoints to more human readable content.
 // e.g. Bock and Transaction which is in protobuf bytes will be formatted and beautified.
-// e.g. Analytics result which should be in vector of u64 return comes in vector of u8 when return from API. We also had to transalte it back. 
-async fn query_helper(route: String, data: Option<Vec<u8>>, query_return_type:QueryReturnType, client: reqwest::Client) -> String{
+// If `save` is given, the raw Borsh bytes of Blocks, Transactions and proofs are also saved as Prt files.
+async fn query_helper(route: String, data: Option<Vec<u8>>, query_return_type:QueryReturnType, save: Option<&str>, client: reqwest::Client) -> String{
 
-    let get_future = if data.is_some() {
-        client.get(route).body(data.unwrap()).send()
+    let get_future = if let Some(data) = data {
+        client.get(route).body(data).send()
     } else {
         client.get(route).send()
     };
@@ -265,47 +258,59 @@ async fn query_helper(route: String, data: Option<Vec<u8>>, query_return_type:Qu
         Ok(some_resp) => some_resp,
         Err(e) => {
             println!("Error: Server connection error");
-            println!("Detail: {}", format!("{}", e));
+            println!("Detail: {}", e);
             std::process::exit(1);
         }
     };
 
+    // Prt bytes saved to standard output are not mixed with the human readable result.
+    let to_stdout = save == Some(input::STDIN);
+
     if resp.status().is_success() {
         match query_return_type {
-            QueryReturnType::Blocks => {
+            QueryReturnType::Blocks(encoding) => {
                 let blocks_return = Vec::<protocol_types::Block>::deserialize(&resp.bytes().await.unwrap()).unwrap();
-                let blocks_print: Blocks = From::<  Vec::<protocol_types::Block> >::from(blocks_return);
-                println!("Your Blocks: {:#?}", blocks_print);
+                if let Some(save) = save {
+                    save_prts(save, blocks_return.iter().map(|block| (Some(block.header.block_number.to_string()), "blk", block.serialize())).collect());
+                }
+                if !to_stdout {
+                    let blocks_print = Blocks::encoded_from(blocks_return, encoding);
+                    println!("Your Blocks: {:#?}", blocks_print);
+                }
             },
             QueryReturnType::BlockHeaders => {
                 let blocks_return = Vec::<protocol_types::BlockHeader>::deserialize(&resp.bytes().await.unwrap()).unwrap();
-                let blocks_print: BlockHeaders = From::<  Vec::<protocol_types::BlockHeader> >::from(blocks_return);
-                println!("Your Blocks (header only): {:#?}", blocks_print);
+                if let Some(save) = save {
+                    save_prts(save, blocks_return.iter().map(|header| (Some(header.block_number.to_string()), "bh", header.serialize())).collect());
+                }
+                if !to_stdout {
+                    let blocks_print: BlockHeaders = From::<  Vec::<protocol_types::BlockHeader> >::from(blocks_return);
+                    println!("Your Blocks (header only): {:#?}", blocks_print);
+                }
             },
             QueryReturnType::BlockSummary => {
                 let value = &resp.text().await.unwrap();
                 let block_summarys: Vec<BlockSummary> = serde_json::from_str(value.as_str()).unwrap();
                 println!("Your Block Summary: {:#?}", block_summarys);
             },
-            QueryReturnType::Transactions => {
+            QueryReturnType::Transactions(encoding) => {
                 let tx_return = Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)>::deserialize(&resp.bytes().await.unwrap()).unwrap();
-                let tx_print: TransactionsWithReceipt = From::< Vec::<(u64, protocol_types::Transaction, protocol_types::Receipt)> >::from(tx_return);
-                println!("Your Txs: {:#?}", tx_print);
+                if let Some(save) = save {
+                    save_prts(save, tx_return.iter().flat_map(|(tx_num, tx, receipt)| vec![
+                        (Some(tx_num.to_string()), "tx", tx.serialize()),
+                        (Some(tx_num.to_string()), "recp", receipt.serialize()),
+                    ]).collect());
+                }
+                if !to_stdout {
+                    let tx_print = TransactionsWithReceipt::encoded_from(tx_return, encoding);
+                    println!("Your Txs: {:#?}", tx_print);
+                }
             },
             QueryReturnType::TransactionSummary => {
                 let value = &resp.text().await.unwrap();
                 let txn_summarys: Vec<TxnSummary> = serde_json::from_str(value.as_str()).unwrap();
                 println!("Your Txn Summary: {:#?}", txn_summarys);
             },
-            QueryReturnType::VectorU64 => {
-                let value = &resp.bytes().await.unwrap().to_vec();
-                let u64vec_from_u8bytes:Vec<u64> = (0..value.len()/8).map(
-                    |index|{
-                        u64::from_le_bytes(value[8*index..8*(index+1)].try_into().unwrap())
-                    }
-                ).collect();
-                println!("Your value {:?}", u64vec_from_u8bytes);
-            },
             QueryReturnType::Binary => {
                 let value = &resp.bytes().await.unwrap().to_vec();
                 match fs::write("contract-code.bin",value) {
@@ -329,6 +334,28 @@ async fn query_helper(route: String, data: Option<Vec<u8>>, query_return_type:Qu
                 println!("Your value {:?}", value);
                 println!("Your value(decoded) {:?}", decoded_value);
             }
+            QueryReturnType::Prt(ext) => {
+                let value = &resp.text().await.unwrap();
+                let decoded_value = match protocol_types::Base64URL::decode(value){
+                    Ok(decode) => decode,
+                    Err(_) => {
+                        println!("Your value is: {:?}", value);
+                        std::process::exit(1);
+                    }
+                };
+                let parsed_value = prt_parser::to_display_types(&decoded_value, ext, BytesEncoding::Auto);
+                if parsed_value.is_empty() {
+                    println!("Error: Unable to save result. The value is not of data type `{}`.", ext);
+                    std::process::exit(1);
+                }
+                if let Some(save) = save {
+                    save_prts(save, vec![(None, ext, decoded_value)]);
+                }
+                if !to_stdout {
+                    println!("Your value {:?}", value);
+                    println!("Your value(parsed) {}", parsed_value);
+                }
+            }
             QueryReturnType::NumberU64 => {
                 let value = &resp.bytes().await.unwrap().to_vec();
                 let numeric_value = {
@@ -342,12 +369,20 @@ async fn query_helper(route: String, data: Option<Vec<u8>>, query_return_type:Qu
                 let value = &resp.bytes().await.unwrap().to_vec();
 
                 let encoded = protocol_types::Base64URL::encode(value);
-                println!("Your callback value (encoded): {}\n", encoded.to_string());
-
-                args_parser::from_callback(encoded.to_string(), "data_type".to_string());
-
-                if let Ok(s) = String::from_utf8(value.clone()) {
-                    println!("Your value (utf8 representation): {}", s);
+                println!("Your callback value (encoded): {}\n", encoded);
+
+                match args_parser::callback_return_value(value) {
+                    Some(return_value) => {
+                        println!("Your return value (raw bytes): {:?}", return_value);
+                        if let Ok(s) = String::from_utf8(return_value) {
+                            println!("Your return value (utf8 representation): {}", s);
+                        }
+                    },
+                    None => {
+                        if let Ok(s) = String::from_utf8(value.clone()) {
+                            println!("Your value (utf8 representation): {}", s);
+                        }
+                    }
                 }
 
                 return encoded.to_string()
@@ -358,7 +393,68 @@ async fn query_helper(route: String, data: Option<Vec<u8>>, query_return_type:Qu
         let resp_detail = resp.text().await.unwrap();
         println!("Query Error. Detail: {}", http_formatted(resp_detail));
     } 
-    return "".to_string()
+    "".to_string()
+}
+
+// Save prts writes the Borsh bytes in `prts`, each with an optional label and its data type extension, as Prt files at `save`
+// (see prt_parser::prt_path), or to standard output if `save` is `-`, which takes exactly one Prt. Nothing is written if
+// any of the files already exists, so that results saved earlier are not overwritten.
+fn save_prts(save: &str, prts: Vec<(Option<String>, &str, Vec<u8>)>) {
+    if input::is_stdin(save) {
+        if prts.len() != 1 {
+            println!("Error: Unable to save result to standard output. The result has {} Prt files, but `{}` takes exactly one.", prts.len(), input::STDIN);
+            std::process::exit(1);
+        }
+        use std::io::Write;
+        if let Err(e) = std::io::stdout().write_all(&prts[0].2) {
+            println!("Error: Unable to save result to standard output. {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let paths: Vec<String> = prts.iter().map(|(label, ext, _)| prt_parser::prt_path(save, label.as_deref(), ext)).collect();
+    if let Some(path) = paths.iter().find(|path| std::path::Path::new(path).exists()) {
+        println!("Error: Unable to save result to `{}`. The file already exists.", path);
+        std::process::exit(1);
+    }
+    for (path, (_, _, data)) in paths.iter().zip(prts.iter()) {
+        match fs::write(path, data) {
+            Ok(_) => println!("Your result is saved to `{}`", path),
+            Err(e) => {
+                println!("Error: Unable to save result to `{}`. {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+// Query nonce returns the number of Transactions from an External Account included on Chain, for
+// commands that need the nonce as an input rather than printing it.
+pub(crate) async fn query_nonce(address: &str) -> u64 {
+    let route = format!("{}/account/{}/nonce?proof=false", setup::read_config(ConfigField::TargetUrl), address);
+    let resp = match reqwest::Client::new().get(route).send().await {
+        Ok(some_resp) => some_resp,
+        Err(e) => {
+            println!("Error: Server connection error");
+            println!("Detail: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if !resp.status().is_success() {
+        println!("Query Error. Status: {:?}", resp.status());
+        let resp_detail = resp.text().await.unwrap();
+        println!("Query Error. Detail: {}", http_formatted(resp_detail));
+        std::process::exit(1);
+    }
+    let value = resp.bytes().await.unwrap().to_vec();
+    match value.as_slice().try_into() {
+        Ok(buf) => u64::from_le_bytes(buf),
+        Err(_) => {
+            println!("Error: Unexpected nonce returned from server: {:?}", value);
+            std::process::exit(1);
+        }
+    }
 }
 
 // Http formatted beautified the html string return from server when it encounter errors.
@@ -372,7 +468,7 @@ pub(crate) fn http_formatted(resp_detail: String) -> String {
             |char| {
                 if char == '<' {within_html_tag_flag = true}
                 else if char == '>' {within_html_tag_flag = false}
-                else if within_html_tag_flag == false {
+                else if !within_html_tag_flag {
                     resp_formatted.push(char);
                 }
             }
@@ -380,5 +476,5 @@ pub(crate) fn http_formatted(resp_detail: String) -> String {
     } else {
         resp_formatted = resp_detail;
     };
-    return resp_formatted;
+    resp_formatted
 }
\ No newline at end of file
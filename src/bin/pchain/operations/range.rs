@@ -0,0 +1,116 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use futures::StreamExt;
+
+use crate::{display_types::{self, BytesEncoding, EncodedFrom, TransactionWithReceipt}, operations::fetch::{self, BlockMode}, setup::{self, ConfigField}};
+
+// Range module queries every Block or Transaction in a range of numbers. The range is split into
+// pages which are fetched concurrently, and each page is printed as soon as it arrives in order.
+
+pub struct PageRange {
+    pub from: u64,
+    // Defaults to the latest Block or Transaction.
+    pub to: Option<u64>,
+    pub page_size: u64,
+    pub concurrency: usize,
+}
+
+// Query block range prints the Blocks numbered `range.from` to `range.to` (inclusive).
+pub async fn query_block_range(mode: BlockMode, range: PageRange, json: bool, encoding: BytesEncoding) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+    let to = match range.to {
+        Some(to) => to,
+        None => fetch::or_exit(fetch::latest_block_number(&client, &rich_api_url).await),
+    };
+    check_range(range.from, to);
+
+    let mut pages = Box::pin(fetch::paged(range.from, to, range.page_size, range.concurrency, |start, window| {
+        fetch::blocks_in_mode(&client, &rich_api_url, &mode, start, window)
+    }));
+    while let Some(((start, window), page)) = pages.next().await {
+        let mut blocks = or_resume(page, start);
+        blocks.retain(|block| fetch::in_page((start, window), block.block_number()));
+        blocks.sort_by_key(|block| block.block_number());
+        for block in blocks {
+            block.print(json, encoding);
+        }
+    }
+}
+
+// Query tx range prints the Transactions numbered `range.from` to `range.to` (inclusive), with their Receipts.
+pub async fn query_tx_range(summary_only: bool, range: PageRange, json: bool, encoding: BytesEncoding) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+    let to = match range.to {
+        Some(to) => to,
+        None => fetch::or_exit(fetch::latest_tx_number(&client, &rich_api_url).await),
+    };
+    check_range(range.from, to);
+
+    if summary_only {
+        let mut pages = Box::pin(fetch::paged(range.from, to, range.page_size, range.concurrency, |start, window| {
+            let (client, rich_api_url) = (&client, &rich_api_url);
+            async move { fetch::transaction_summaries(client, rich_api_url, &format!("num={}", start), window).await }
+        }));
+        while let Some(((start, window), page)) = pages.next().await {
+            let mut summaries = or_resume(page, start);
+            summaries.retain(|summary| fetch::in_page((start, window), summary.number));
+            summaries.sort_by_key(|summary| summary.number);
+            for summary in summaries {
+                fetch::print_item(&summary, json);
+            }
+        }
+    } else {
+        let mut pages = Box::pin(fetch::paged(range.from, to, range.page_size, range.concurrency, |start, window| {
+            let (client, rich_api_url) = (&client, &rich_api_url);
+            async move { fetch::transactions(client, rich_api_url, &format!("num={}", start), window).await }
+        }));
+        while let Some(((start, window), page)) = pages.next().await {
+            let mut txs = or_resume(page, start);
+            txs.retain(|(tx_num, _, _)| fetch::in_page((start, window), *tx_num));
+            txs.sort_by_key(|(tx_num, _, _)| *tx_num);
+            for (tx_num, tx, receipt) in txs {
+                fetch::print_item(&TransactionWithReceipt {
+                    tx_num,
+                    transaction: display_types::Transaction::from(tx),
+                    receipt: display_types::Receipt::encoded_from(receipt, encoding),
+                }, json);
+            }
+        }
+    }
+}
+
+fn check_range(from: u64, to: u64) {
+    if from > to {
+        println!("Error: --from ({}) is greater than --to ({}).", from, to);
+        std::process::exit(1);
+    }
+}
+
+// Or resume exits on a failed page. Everything before `start` has been printed already,
+// so the query can be resumed from there.
+fn or_resume<T>(page: Result<Vec<T>, String>, start: u64) -> Vec<T> {
+    match page {
+        Ok(items) => items,
+        Err(e) => {
+            println!("Error: {}", e);
+            println!("Resume with --from {}", start);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use crate::{display_types::{SeriesPoint, SeriesReport}, operations::fetch, setup::{self, ConfigField}, stats, time_parser};
+
+// Series module presents the moving-window series of the analytics API. The API returns one value per
+// window, so the start time of each window is reconstructed from the start time and the step size.
+
+// Widest chart (in columns) printed. Longer series are averaged down to this width.
+const CHART_WIDTH: usize = 100;
+const CHART_HEIGHT: usize = 12;
+
+pub enum AnalyticsSeries {
+    GasPerBlock,
+    MempoolSize,
+}
+
+impl AnalyticsSeries {
+    fn category(&self) -> &'static str {
+        match self {
+            AnalyticsSeries::GasPerBlock => "gas_per_block",
+            AnalyticsSeries::MempoolSize => "mempool_size",
+        }
+    }
+}
+
+pub struct SeriesOutput {
+    pub json: bool,
+    pub chart: bool,
+    // Path of a CSV file to write the series to.
+    pub csv: Option<String>,
+}
+
+// Analyze series prints the values of `series` from `start_time` to `end_time`, each with its window,
+// followed by summary statistics and a chart.
+pub async fn analyze_series(series: AnalyticsSeries, start_time: u64, end_time: u64, window_size: u64, step_size: u64, output: SeriesOutput) {
+    let client = reqwest::Client::new();
+    let analytics_api_url = setup::read_config(ConfigField::AnalyticsApiUrl);
+    let values = fetch::or_exit(fetch::analytics_series(&client, &analytics_api_url, series.category(), start_time, end_time, window_size, step_size).await);
+
+    let points: Vec<SeriesPoint> = values.iter().enumerate().map(|(i, value)| {
+        let window_start = start_time + i as u64 * step_size;
+        SeriesPoint { window_start, window_end: window_start + window_size, value: *value }
+    }).collect();
+    let values: Vec<f64> = values.iter().map(|v| *v as f64).collect();
+    let report = SeriesReport {
+        series: series.category().to_string(),
+        window_size,
+        step_size,
+        points,
+        summary: stats::distribution(&values),
+    };
+
+    if let Some(path) = &output.csv {
+        let mut content = String::from("window_start,window_end,value\n");
+        for point in &report.points {
+            content.push_str(&format!("{},{},{}\n", time_parser::format_timestamp(point.window_start), time_parser::format_timestamp(point.window_end), point.value));
+        }
+        match std::fs::write(path, content) {
+            // Keep stdout parseable in JSON mode.
+            Ok(_) if output.json => eprintln!("Your series is saved to {}", path),
+            Ok(_) => println!("Your series is saved to {}", path),
+            Err(e) => {
+                println!("Error: Fail to write {}. {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if output.json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!("{:>20} {:>20} {:>16}", "Window Start", "Window End", "Value");
+    for point in &report.points {
+        println!("{:>20} {:>20} {:>16}", time_parser::format_timestamp(point.window_start), time_parser::format_timestamp(point.window_end), point.value);
+    }
+    println!();
+    match &report.summary {
+        Some(summary) => println!("Summary: {}", summary),
+        None => {
+            println!("Summary: no data");
+            return;
+        }
+    }
+    let resampled = stats::resample(&values, CHART_WIDTH);
+    println!("Trend: {}", stats::sparkline(&resampled));
+    if output.chart {
+        println!();
+        for line in stats::line_chart(&resampled, CHART_HEIGHT) {
+            println!("{}", line);
+        }
+    }
+}
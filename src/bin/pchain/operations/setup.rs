@@ -15,22 +15,14 @@
  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use home; 
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 const PCHAIN_CLI_CONFIG_PATH: &str = ".parallelchain/pchain_cli/config.json";
+const PCHAIN_CLI_ADDRESS_BOOK_PATH: &str = ".parallelchain/pchain_cli/address_book.json";
 
 // Config.json fields for pchain
-#[derive(Serialize, Deserialize)]
-struct Config {
-    target_url: String,
-    rich_api_url: String,
-    analytics_api_url: String,
-    keypair_json_path: String,
-}
-
 pub enum ConfigField {
     TargetUrl,
     RichApiUrl,
@@ -38,9 +30,9 @@ pub enum ConfigField {
     KeypairJSONPath,
 }
 
-impl Into<String> for &ConfigField{
-    fn into(self) -> String {
-        match self {
+impl From<&ConfigField> for String{
+    fn from(val: &ConfigField) -> Self {
+        match val {
             ConfigField::TargetUrl => "target_url".to_string(),
             ConfigField::RichApiUrl => "rich_api_url".to_string(),
             ConfigField::AnalyticsApiUrl => "analytics_api_url".to_string(),
@@ -53,7 +45,7 @@ impl Into<String> for &ConfigField{
 // If no, it creates the file and path.
 // Then it write the corresponding data field to config.json
 pub fn set_config(field: ConfigField, field_value: &str){
-    let mut default_config_path = PathBuf::from(home::home_dir().expect("Fail to find home directory. The home path might belong to root"));
+    let mut default_config_path = home::home_dir().expect("Fail to find home directory. The home path might belong to root");
     default_config_path.push(PCHAIN_CLI_CONFIG_PATH);
 
     if !Path::new(&default_config_path).is_file(){
@@ -66,7 +58,7 @@ pub fn set_config(field: ConfigField, field_value: &str){
                 }
             };
         };
-        match std::fs::File::create(&default_config_path.clone()){
+        match std::fs::File::create(default_config_path.clone()){
             Ok(_) => {},
             Err(e) => {
                 println!("Error: Cannot create config file. {}", e);
@@ -108,7 +100,7 @@ pub fn set_config(field: ConfigField, field_value: &str){
 // Read config first figure out whether the file `HOME/.parallelchain/pchain_cli/config.json` exist.
 // Then it read the corresponding data field to config.json if it exists.
 pub fn read_config(config_var: ConfigField) -> String {
-    let mut default_config_path = PathBuf::from(home::home_dir().expect("The home path might belong to root"));
+    let mut default_config_path = home::home_dir().expect("The home path might belong to root");
     default_config_path.push(PCHAIN_CLI_CONFIG_PATH);
 
     let config = match std::fs::File::open(default_config_path){
@@ -136,3 +128,15 @@ pub fn read_config(config_var: ConfigField) -> String {
     };
     field_interested
 }
+
+// Read address book reads `HOME/.parallelchain/pchain_cli/address_book.json`, a json object of named
+// entries referenced by Transaction templates, e.g. {"treasury": {"address": "..."}}.
+pub fn read_address_book() -> Result<Value, String> {
+    let mut address_book_path = home::home_dir().expect("The home path might belong to root");
+    address_book_path.push(PCHAIN_CLI_ADDRESS_BOOK_PATH);
+
+    let address_book = std::fs::read_to_string(&address_book_path)
+        .map_err(|e| format!("Cannot read address book {}. {}", address_book_path.display(), e))?;
+    serde_json::from_str(&address_book)
+        .map_err(|e| format!("The address book {} should be a proper json. {}", address_book_path.display(), e))
+}
@@ -14,14 +14,14 @@
  You should have received a copy of the GNU General Public License
  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use std::{io::Error, path::Path, convert::TryInto};
+use std::{io::Error, convert::TryInto};
 use serde::{Serialize, Deserialize};
 use sha2::{Digest, Sha256};
 use ed25519_dalek::Signer;
 
 use protocol_types::{Serializable, crypto, PublicAddress};
 
-use crate::{setup::{ConfigField, self}, operations::{query::http_formatted, KeypairJSON}, Base64String};
+use crate::{setup::{ConfigField, self}, operations::{query::{self, http_formatted}, KeypairJSON}, contract_validator, file_format::FileFormat, input, tx_template::{self, TemplateVars}, Base64String};
 
 // Submit module handles transaction submit request.
 // It formats the request body and assemble the http request for submit.
@@ -33,6 +33,26 @@ use crate::{setup::{ConfigField, self}, operations::{query::http_formatted, Keyp
 pub async fn submit(tx_json: SubmitTxJson, is_deploy: bool) {
     let tx_json_string = serde_json::to_string_pretty(&tx_json).unwrap();
 
+    let tx_data = match sign_tx_json(tx_json, is_deploy) {
+        Ok((tx_data, _)) => tx_data,
+        Err(e) => {
+            println!("The Transaction has wrong format or unexpcted problem encountered. The transaction is not sent.");
+            println!("Error: {:?}", e.to_string());
+            return;
+        }
+    };
+
+    let route_address_raw = setup::read_config(ConfigField::TargetUrl);
+    let route_address = route_address_raw;
+    let client = reqwest::Client::new();
+    let api_url = format!("{}/transaction", route_address);
+    println!("Submit Transaction {}", tx_json_string);
+    post_transaction(client, tx_data, api_url).await;
+}
+
+// Sign tx json parses the fields of `tx_json` and returns the signed, serialized Transaction.
+// Sign tx json returns the serialized and signed Transaction of `tx_json`, and its hash.
+pub(crate) fn sign_tx_json(tx_json: SubmitTxJson, is_deploy: bool) -> Result<(Vec<u8>, [u8; 32]), Error> {
     let sender_address = parse_sender_address(tx_json.from_address);
     let (receiver_address, data) = if is_deploy { 
         let (contract_address, data) = parse_contract(sender_address, tx_json.nonce, tx_json.data, tx_json.deploy_args);
@@ -41,7 +61,7 @@ pub async fn submit(tx_json: SubmitTxJson, is_deploy: bool) {
     } else {
         (parse_eoa_receiver_address(tx_json.to_address.clone()), parse_tx_data(tx_json.data))
     };
-    let tx_data = match build_protocol_types_tx(
+    build_protocol_types_tx(
         sender_address, 
         receiver_address, 
         tx_json.value, 
@@ -50,23 +70,65 @@ pub async fn submit(tx_json: SubmitTxJson, is_deploy: bool) {
         tx_json.gas_price, 
         data, 
         tx_json.nonce, 
-        tx_json.path_to_keypair_json) {
-            Ok(tx_data) => tx_data,
-            Err(e) => {
-                println!("The Transaction has wrong format or unexpcted problem encountered. The transaction is not sent.");
-                println!("Error: {:?}", e.to_string());
-                return;
-            }
-        };
+        tx_json.path_to_keypair_json)
+}
+
+// Replace re-signs the Transaction in `tx_json` at `nonce` with a higher tip (and optionally a new gas price),
+// so that it takes the place of a Transaction with the same nonce which is stuck in the mempool.
+pub async fn replace(mut tx_json: SubmitTxJson, nonce: u64, tip: u64, gas_price: Option<u64>) {
+    if let Err(e) = check_tip_bump(tx_json.tip, tip) {
+        println!("Error: {}", e);
+        std::process::exit(1);
+    }
+    check_pending_nonce(&tx_json.from_address, nonce).await;
+    tx_json.nonce = nonce;
+    tx_json.tip = tip;
+    if let Some(gas_price) = gas_price {
+        tx_json.gas_price = gas_price;
+    }
+    let is_deploy = &tx_json.to_address == "null"; // To address is null if and only if it is a deploy transaction
+    submit(tx_json, is_deploy).await
+}
+
+// Check tip bump is a sanity check that the `tip` of a replacing Transaction is higher than `replaced_tip`, the tip
+// given for the Transaction it replaces. The tip of the stuck Transaction itself is not known to the CLI.
+fn check_tip_bump(replaced_tip: u64, tip: u64) -> Result<(), String> {
+    if tip <= replaced_tip {
+        return Err(format!("'tip' ({}) is not higher than the given tip of the Transaction being replaced ({}).", tip, replaced_tip));
+    }
+    Ok(())
+}
+
+// Cancel sends a zero-value Transaction from `from_address` to itself at `nonce`. Once it is included, the
+// Transaction stuck at the same nonce can no longer be included.
+pub async fn cancel(from_address: String, nonce: u64, tip: u64, gas_limit: u64, gas_price: u64, path_to_keypair_json: String) {
+    check_pending_nonce(&from_address, nonce).await;
+    let sender_address = parse_sender_address(from_address.clone());
+    let tx_data = match build_protocol_types_tx(sender_address, sender_address, 0, tip, gas_limit, gas_price, vec![], nonce, path_to_keypair_json) {
+        Ok((tx_data, _)) => tx_data,
+        Err(e) => {
+            println!("The Transaction has wrong format or unexpcted problem encountered. The transaction is not sent.");
+            println!("Error: {:?}", e.to_string());
+            return;
+        }
+    };
 
-    let route_address_raw = setup::read_config(ConfigField::TargetUrl);
-    let route_address = route_address_raw;
     let client = reqwest::Client::new();
-    let api_url = format!("{}/transaction", route_address);
-    println!("Submit Transaction {}", tx_json_string);
+    let api_url = format!("{}/transaction", setup::read_config(ConfigField::TargetUrl));
+    println!("Submit cancellation of nonce {} from {}", nonce, from_address);
     post_transaction(client, tx_data, api_url).await;
 }
 
+// Check pending nonce exits with an error if a Transaction with `nonce` from `from_address` is already
+// included on Chain, in which case there is nothing left to replace.
+async fn check_pending_nonce(from_address: &str, nonce: u64) {
+    let next_nonce = query::query_nonce(from_address).await;
+    if nonce < next_nonce {
+        println!("Error: The Transaction with nonce {} from {} is already included on Chain (next nonce is {}). It cannot be replaced.", nonce, from_address, next_nonce);
+        std::process::exit(1);
+    }
+}
+
 async fn post_transaction(client: reqwest::Client, tx_data: Vec<u8>, api_url: String) {
     let resp = match client.post(api_url)
     .body(tx_data)
@@ -75,7 +137,7 @@ async fn post_transaction(client: reqwest::Client, tx_data: Vec<u8>, api_url: St
         Ok(some_resp) => some_resp,
         Err(e) => {
             println!("Error: Server connection error");
-            println!("Detail: {}", format!("{}", e));
+            println!("Detail: {}", e);
             std::process::exit(1);
         }
     };
@@ -89,7 +151,21 @@ async fn post_transaction(client: reqwest::Client, tx_data: Vec<u8>, api_url: St
     }
 }
 
-fn parse_sender_address(from_address: String) -> PublicAddress {
+// Send transaction posts a signed Transaction to the fullnode, returning the response on success
+// and a description of the failure otherwise, for callers that submit more than one Transaction.
+pub(crate) async fn send_transaction(client: &reqwest::Client, tx_data: Vec<u8>, api_url: &str) -> Result<String, String> {
+    let resp = client.post(api_url).body(tx_data).send().await
+        .map_err(|e| format!("Server connection error. {}", e))?;
+    let status = resp.status();
+    let resp_detail = resp.text().await.unwrap_or_default();
+    if status.is_success() {
+        Ok(resp_detail)
+    } else {
+        Err(format!("Status: {:?}. Detail: {}", status, http_formatted(resp_detail)))
+    }
+}
+
+pub(crate) fn parse_sender_address(from_address: String) -> PublicAddress {
     match protocol_types::Base64URL::decode(&from_address.clone()){
         Ok(address) => match address.try_into() {
             Ok(address) => address,
@@ -132,15 +208,46 @@ fn parse_tx_data(data_string: String) -> Vec<u8> {
 }
 
 fn parse_contract(sender_address: crypto::PublicAddress, nonce: u64, data_string: String, deploy_args_string: String) -> (Base64String, Vec<u8>) {
-    let contract_address: Base64String;
-    let contract_code = match data_string.chars().next() {
+    let contract_code = load_contract_code(data_string);
+
+    let deploy_args = match protocol_types::Base64URL::decode(&deploy_args_string.clone()){
+        Ok(decode_data) => decode_data,
+        Err(_) => {
+            println!("Error: deploy-args of your input is not basae64 decodable.");
+            std::process::exit(-1);
+        }
+    };
+
+    // Reject contracts which cannot possibly be executed before paying for the deployment.
+    match contract_validator::validate(&contract_code, !deploy_args.is_empty()) {
+        Ok(summary) => println!("{}", summary),
+        Err(e) => {
+            println!("Error: Invalid contract. {}. The transaction is not sent.", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Data in protocol_types::transaction will be seperated into contract bytecodes and arguments for 'init' entrypoint of the contract
+    let transaction_data = protocol_types::transaction::TransactionDataContractDeployment {
+        contract_code: contract_code.clone(),
+        contract_init_arguments: deploy_args
+    };
+
+    let data = protocol_types::TransactionDataContractDeployment::serialize(&transaction_data);
+
+    (contract_address(&contract_code, &sender_address, nonce), data)
+}
+
+// Load contract code either from a relative path (starting with `.`) to a '.wasm' file, from standard input (`-`), or from a base64 encoded string.
+pub(crate) fn load_contract_code(data_string: String) -> Vec<u8> {
+    match data_string.chars().next() {
         Some(char) => {
             let char_str = char.to_string();
             // As required by cli, importing contract by path must start with `./` to indicate a relative path.
             // Else it will be read as base64 encoded file bytes
-            if char_str == ".".to_string(){
-                if Path::new(&data_string).is_file(){
-                    match std::fs::read(&data_string) {
+            if char_str == "." || input::is_stdin(&data_string) {
+                if input::exists(&data_string){
+                    match input::read(&data_string) {
                         Ok(data) => data,
                         Err(e) => {
                             println!("Error: : Fail to read designated file although is file found {:?}", e);
@@ -163,39 +270,24 @@ fn parse_contract(sender_address: crypto::PublicAddress, nonce: u64, data_string
             }
         },
         None => {println!("Error: Data is required for Deploy transaction"); std::process::exit(1)}
-    };
-
-    let deploy_args = match protocol_types::Base64URL::decode(&deploy_args_string.clone()){
-        Ok(decode_data) => decode_data,
-        Err(_) => {
-            println!("Error: deploy-args of your input is not basae64 decodable.");
-            std::process::exit(-1);
-        }
-    };
-
-    // Data in protocol_types::transaction will be seperated into contract bytecodes and arguments for 'init' entrypoint of the contract
-    let transaction_data = protocol_types::transaction::TransactionDataContractDeployment {
-        contract_code: contract_code.clone(),
-        contract_init_arguments: deploy_args
-    };
-
-    let data = protocol_types::TransactionDataContractDeployment::serialize(&transaction_data);
+    }
+}
 
+// The address of a deployed contract is sha256(contract code || sender address || nonce in little endian).
+pub(crate) fn contract_address(contract_code: &[u8], sender_address: &crypto::PublicAddress, nonce: u64) -> Base64String {
     let mut hasher = Sha256::new();
     let mut pre_image = Vec::new();
-    pre_image.extend(&contract_code);
-    pre_image.extend(&sender_address);
+    pre_image.extend(contract_code);
+    pre_image.extend(sender_address);
     pre_image.extend(nonce.to_le_bytes().to_vec());
 
     hasher.update(pre_image);
 
     let contract_protoaddr = hasher.finalize().to_vec();
-    contract_address = protocol_types::Base64URL::encode(contract_protoaddr).to_string();
-    (contract_address, data)
-    // println!("Contract address: {:?}", contract_address);
-    
+    protocol_types::Base64URL::encode(contract_protoaddr).to_string()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_protocol_types_tx(
     sender_address: crypto::PublicAddress, 
     receiver_address: crypto::PublicAddress, 
@@ -206,14 +298,14 @@ fn build_protocol_types_tx(
     data: Vec<u8>,
     nonce: u64, 
     path_to_keypair_json: String) 
-    ->  Result<Vec<u8>, Error> {
+    ->  Result<(Vec<u8>, [u8; 32]), Error> {
     let tx_protocol_type = protocol_types::transaction::Transaction {
         from_address: sender_address,
         to_address: receiver_address,
-        value: value,
-        tip: tip,
-        gas_limit: gas_limit,
-        gas_price: gas_price,
+        value,
+        tip,
+        gas_limit,
+        gas_price,
         data,
         n_txs_on_chain_from_address: nonce,
         // the hash and fields are expected to be populated. Otherwise the node will not
@@ -228,7 +320,8 @@ fn build_protocol_types_tx(
 }
     
 /// hash_and_sign_transaction obviously hash and sign transactions and serialize transaction to pchain-types-encoded bytes.
-fn hash_and_sign_transaction(keypair_base64_string: String, mut transaction: protocol_types::transaction::Transaction ) -> Result<Vec<u8>, Error>{
+/// The hash of the transaction is returned along with the bytes.
+pub(crate) fn hash_and_sign_transaction(keypair_base64_string: String, mut transaction: protocol_types::transaction::Transaction ) -> Result<(Vec<u8>, [u8; 32]), Error>{
     // Retrieve the keypair from base64 string
     let keypair_raw = match protocol_types::Base64URL::decode(&keypair_base64_string){
         Ok(address) => address,
@@ -245,30 +338,36 @@ fn hash_and_sign_transaction(keypair_base64_string: String, mut transaction: pro
         }
     };
 
-    // Serialize the transaction for signature verification.
-    let serialized_transaction = protocol_types::transaction::Transaction::serialize(&transaction);
-
-    let signature: ed25519_dalek::Signature = keypair.sign(&serialized_transaction[..]);
-    println!("Signature of tx: {:?}", protocol_types::Base64URL::encode(&signature).to_string());
-
-    // Compute the hash of transaction
-    let mut hasher = Sha256::new();
-    hasher.update(&signature);
-    let computed_hash = hasher.finalize();
+    let signature: ed25519_dalek::Signature = keypair.sign(&signing_bytes(&transaction));
+    println!("Signature of tx: {:?}", protocol_types::Base64URL::encode(signature).to_string());
 
-    println!("Hash of tx: {:?}", protocol_types::Base64URL::encode(&computed_hash).to_string());
-
-    transaction.hash = computed_hash.into();
     transaction.signature = signature.to_bytes();
+    transaction.hash = transaction_hash(&transaction.signature);
+    println!("Hash of tx: {:?}", protocol_types::Base64URL::encode(transaction.hash).to_string());
 
     let output_serialize_tx_data = protocol_types::Transaction::serialize(&transaction);
 
-    Ok(output_serialize_tx_data)
+    Ok((output_serialize_tx_data, transaction.hash))
+}
+
+// Signing bytes are the bytes which the sender of a Transaction signs: the serialized Transaction with `hash` and `signature` zeroed.
+pub(crate) fn signing_bytes(transaction: &protocol_types::Transaction) -> Vec<u8> {
+    let mut unsigned_transaction = transaction.clone();
+    unsigned_transaction.hash = [0; 32];
+    unsigned_transaction.signature = [0; 64];
+    protocol_types::Transaction::serialize(&unsigned_transaction)
+}
+
+// The hash of a Transaction is the SHA256 of its signature.
+pub(crate) fn transaction_hash(signature: &[u8; 64]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(signature);
+    hasher.finalize().into()
 }
 
 fn load_keypair(path_to_keypair_json: String) -> String {
-    let keypair_base64_string = if Path::new(&path_to_keypair_json).is_file(){
-        match std::fs::read(&path_to_keypair_json) {
+    let keypair_base64_string = if input::exists(&path_to_keypair_json){
+        match input::read(&path_to_keypair_json) {
             Ok(data) => match String::from_utf8(data) {
                 Ok(keypair_json) => {
                     match serde_json::from_str::<KeypairJSON>(keypair_json.as_str()){
@@ -311,19 +410,22 @@ pub struct SubmitTxJson {
 }
 
 impl SubmitTxJson {
-    pub fn load_tx_json_file(path_to_json: String) -> SubmitTxJson{
-        let tx_json = if Path::new(&path_to_json).is_file(){
-            match std::fs::read(&path_to_json) {
+    // Load tx json file reads a Transaction file in json, yaml or toml (see `file_format`), rendering the placeholders in it (see `tx_template`)
+    // with `vars` (each of the form NAME=value), environment variables and the address book.
+    /// Load tx json file reads and renders the tx file at `path_to_json`. If `nonce` is given, `${nonce:auto}` is
+    /// rendered as it instead of querying the nonce of the sender.
+    pub async fn load_tx_json_file(path_to_json: String, vars: &[String], nonce: Option<u64>) -> SubmitTxJson{
+        let vars = match tx_template::parse_vars(vars) {
+            Ok(vars) => vars,
+            Err(e) => {
+                println!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let template = if input::exists(&path_to_json){
+            match input::read(&path_to_json) {
                 Ok(data) => match String::from_utf8(data) {
-                    Ok(keypair_json) => {
-                        match serde_json::from_str::<SubmitTxJson>(keypair_json.as_str()){
-                            Ok(kp_json) => kp_json,
-                            Err(e) => {
-                                println!("Error: : Fail to parse tx json file from json {:?}", e);
-                                std::process::exit(1);
-                            }
-                        }
-                    },
+                    Ok(template) => template,
                     Err(e) => {
                         println!("Error: : Fail to parse tx json file although is file found {:?}", e);
                         std::process::exit(1);  
@@ -338,6 +440,71 @@ impl SubmitTxJson {
             println!("Error: : Invalid path. Cannot retrieve tx json file from the designated path.");
             std::process::exit(1);
         };
-        tx_json
+
+        match render_tx_template(&template, &path_to_json, &vars, nonce).await.and_then(|tx_json| {
+            serde_json::from_value::<SubmitTxJson>(tx_json).map_err(|e| format!("Fail to parse tx file: {}", e))
+        }) {
+            Ok(tx_json) => tx_json,
+            Err(e) => {
+                println!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+// Render tx template renders `template` read from `path`, and parses it in the format of the file. Unless `nonce` is
+// given, the nonce of `${nonce:auto}` is queried after everything else is rendered, as it depends on the rendered "from_address".
+async fn render_tx_template(template: &str, path: &str, vars: &TemplateVars, nonce: Option<u64>) -> Result<serde_json::Value, String> {
+    let mut address_book: Option<serde_json::Value> = None;
+    // Set if `${nonce:auto}` is rendered without the nonce, which is then rendered again once it is known.
+    let needs_nonce = std::cell::Cell::new(false);
+    let mut resolve = |reference: &str, nonce: Option<u64>| -> Result<Option<String>, String> {
+        if let Some(value) = vars.get(reference) {
+            return Ok(Some(value.clone()));
+        }
+        match reference.split_once(':') {
+            None => Ok(std::env::var(reference).ok()),
+            Some(("key", path)) => {
+                if address_book.is_none() {
+                    address_book = Some(setup::read_address_book()?);
+                }
+                match tx_template::lookup(address_book.as_ref().unwrap(), path) {
+                    Some(value) => Ok(Some(value)),
+                    None => Err(format!("`{}` is not a string or number in the address book", path)),
+                }
+            },
+            Some(_) if reference == tx_template::AUTO_NONCE => match nonce {
+                Some(nonce) => Ok(Some(nonce.to_string())),
+                None => {
+                    needs_nonce.set(true);
+                    Ok(Some("0".to_string()))
+                }
+            },
+            Some(_) => Err(format!("unknown reference `{}`. Expected key:<name>.<field> or {}", reference, tx_template::AUTO_NONCE)),
+        }
+    };
+
+    let rendered = tx_template::render(template, |reference| resolve(reference, nonce))?;
+    let format = FileFormat::detect(path, &rendered);
+    let rendered = format.parse(&rendered)?;
+    if !needs_nonce.get() {
+        return Ok(rendered);
+    }
+    let from_address = rendered.get("from_address").and_then(|address| address.as_str())
+        .ok_or_else(|| format!("{} needs \"from_address\" in the tx file", tx_template::AUTO_NONCE))?;
+    let nonce = query::query_nonce(from_address).await;
+    format.parse(&tx_template::render(template, |reference| resolve(reference, Some(nonce)))?)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_check_tip_bump() {
+        assert!(super::check_tip_bump(10, 11).is_ok());
+        assert!(super::check_tip_bump(0, u64::MAX).is_ok());
+        assert!(super::check_tip_bump(10, 10).is_err());
+        assert!(super::check_tip_bump(10, 9).is_err());
+        assert!(super::check_tip_bump(u64::MAX, u64::MAX).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use crate::{display_types::{self, BytesEncoding, EncodedFrom, IncludedTransaction}, operations::{fetch, query}, setup::{self, ConfigField}, Base64Address, Base64Hash};
+
+// Tx status module looks up a single Transaction by hash. A Transaction is either included in a Block,
+// pending, or unknown. The APIs do not look up the mempool by hash, so a Transaction which is not included is
+// told apart by the size of the mempool, and by the nonce of its sender if they are given.
+
+pub async fn query_tx(tx_hash: Base64Hash, sender: Option<(Base64Address, u64)>, json: bool, encoding: BytesEncoding) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+    let target_url = setup::read_config(ConfigField::TargetUrl);
+
+    let hash: Vec<u8> = match protocol_types::Base64URL::decode(&tx_hash) {
+        Ok(hash) if hash.len() == 32 => hash,
+        _ => {
+            println!("Error: 'hash' must be a Base64 encoded hash of 32 bytes.");
+            std::process::exit(1);
+        }
+    };
+
+    // The Rich API answers 404 Not Found for a hash which is not included on chain.
+    let selector = format!("tx_hash={}", tx_hash);
+    let txs = fetch::or_exit(fetch::transactions_if_found(&client, &rich_api_url, &selector, 1).await).unwrap_or_default();
+
+    if let Some((tx_num, tx, receipt)) = find_tx(txs, &hash) {
+        let header = fetch::or_exit(fetch::block_headers(&client, &rich_api_url, &selector, 1).await);
+        let header = match header.into_iter().next() {
+            Some(header) => header,
+            None => {
+                println!("Error: Transaction is included on chain, but its Block could not be found.");
+                std::process::exit(1);
+            }
+        };
+        let included = IncludedTransaction {
+            tx_num,
+            block_number: header.block_number,
+            block_hash: protocol_types::Base64URL::encode(header.this_block_hash).to_string(),
+            transaction: display_types::Transaction::from(tx),
+            receipt: display_types::Receipt::encoded_from(receipt, encoding),
+        };
+        if json {
+            println!("{}", serde_json::json!({ "status": "included", "transaction": included }));
+        } else {
+            println!("Status: included in Block {}", included.block_number);
+            println!("{:#?}", included);
+        }
+        return;
+    }
+
+    let mempool_size = fetch::or_exit(fetch::mempool_size(&client, &target_url).await);
+    let nonces = match &sender {
+        Some((from_address, nonce)) => Some((*nonce, query::query_nonce(from_address).await)),
+        None => None,
+    };
+    let status = not_included_status(mempool_size, nonces);
+
+    if json {
+        println!("{}", serde_json::json!({ "status": status, "hash": tx_hash, "mempool_size": mempool_size }));
+    } else {
+        match (status, nonces) {
+            ("pending", Some((nonce, _))) => println!("Status: pending. Transaction {} is not included in a Block yet. Nonce {} of its sender is not used on chain, and the mempool has {} Transaction(s).", tx_hash, nonce, mempool_size),
+            ("unknown", _) if mempool_size == 0 => println!("Status: unknown. Transaction {} is not in any Block, and the mempool is empty.", tx_hash),
+            ("unknown", Some((nonce, _))) => println!("Status: unknown. Transaction {} is not in any Block, and nonce {} of its sender is already used by another Transaction.", tx_hash, nonce),
+            _ => println!("Status: not included. Transaction {} is not in any Block, and the mempool has {} Transaction(s). Give 'from-address' and 'nonce' to tell whether it is pending.", tx_hash, mempool_size),
+        }
+    }
+    if status != "pending" {
+        std::process::exit(1);
+    }
+}
+
+// Not included status returns the status of a Transaction which is not included in a Block, given the size of the
+// mempool and, if known, its nonce and the nonce of its sender on chain. It is "unknown" if the mempool is empty or
+// its nonce is already used, "pending" if its nonce can still be used, and "not_included" if it cannot be told.
+fn not_included_status(mempool_size: u64, nonces: Option<(u64, u64)>) -> &'static str {
+    match nonces {
+        _ if mempool_size == 0 => "unknown",
+        Some((nonce, chain_nonce)) if nonce < chain_nonce => "unknown",
+        Some(_) => "pending",
+        None => "not_included",
+    }
+}
+
+// Find tx picks the Transaction with `hash` out of the window of Transactions which the Rich API returns around it.
+fn find_tx(txs: Vec<(u64, protocol_types::Transaction, protocol_types::Receipt)>, hash: &[u8]) -> Option<(u64, protocol_types::Transaction, protocol_types::Receipt)> {
+    txs.into_iter().find(|(_, tx, _)| tx.hash[..] == hash[..])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::operations::fixtures;
+
+    fn tx(tx_num: u64, hash: [u8; 32]) -> (u64, protocol_types::Transaction, protocol_types::Receipt) {
+        (tx_num, protocol_types::Transaction { hash, ..fixtures::transaction() }, fixtures::receipt())
+    }
+
+    #[test]
+    fn test_find_tx() {
+        let window = || vec![tx(4, [4u8; 32]), tx(5, [5u8; 32]), tx(6, [6u8; 32])];
+        assert_eq!(super::find_tx(window(), &[5u8; 32]).map(|(tx_num, _, _)| tx_num), Some(5));
+        assert!(super::find_tx(window(), &[7u8; 32]).is_none());
+        assert!(super::find_tx(vec![], &[5u8; 32]).is_none());
+    }
+
+    #[test]
+    fn test_not_included_status() {
+        assert_eq!(super::not_included_status(0, None), "unknown");
+        assert_eq!(super::not_included_status(0, Some((5, 5))), "unknown");
+        assert_eq!(super::not_included_status(3, Some((4, 5))), "unknown");
+        assert_eq!(super::not_included_status(3, Some((5, 5))), "pending");
+        assert_eq!(super::not_included_status(3, Some((7, 5))), "pending");
+        assert_eq!(super::not_included_status(3, None), "not_included");
+    }
+}
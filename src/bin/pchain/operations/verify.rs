@@ -0,0 +1,201 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use ed25519_dalek::Verifier;
+use futures::StreamExt;
+
+use crate::{operations::{fetch, submit}, setup::{self, ConfigField}};
+
+// Verify module checks Blocks returned by the Rich API locally instead of trusting the fullnode. Each Block is
+// checked against the Block before it (hash linkage and numbering), and each Transaction against its signature,
+// signed and hashed in the same way as `submit` does.
+
+// Verify chain prints every problem found in the Blocks numbered `from` to `to` (inclusive),
+// and exits with an error if there is any.
+pub async fn verify_chain(from: u64, to: Option<u64>, page_size: u64, concurrency: usize) {
+    let client = reqwest::Client::new();
+    let rich_api_url = setup::read_config(ConfigField::RichApiUrl);
+    let to = match to {
+        Some(to) => to,
+        None => fetch::or_exit(fetch::latest_block_number(&client, &rich_api_url).await),
+    };
+    if from > to {
+        println!("Error: --from ({}) is greater than --to ({}).", from, to);
+        std::process::exit(1);
+    }
+
+    // The first Block is linked to its parent, if it has one.
+    let mut prev = if from > 0 {
+        fetch::or_exit(fetch::block_headers(&client, &rich_api_url, &format!("num={}", from - 1), 1).await)
+            .into_iter().find(|header| header.block_number == from - 1)
+    } else {
+        None
+    };
+
+    let mut problems = 0;
+    let mut next = from;
+    let mut pages = Box::pin(fetch::block_pages(&client, &rich_api_url, from, to, page_size, concurrency));
+    while let Some((_, page)) = pages.next().await {
+        for block in fetch::or_exit(page) {
+            if block.header.block_number != next {
+                println!("Blocks {} to {} are missing from the Rich API response.", next, block.header.block_number - 1);
+                problems += 1;
+                prev = None;
+            }
+            for problem in check_block(prev.as_ref(), &block) {
+                println!("Block {}: {}", block.header.block_number, problem);
+                problems += 1;
+            }
+            next = block.header.block_number + 1;
+            prev = Some(block.header);
+        }
+    }
+    if next <= to {
+        println!("Blocks {} to {} are missing from the Rich API response.", next, to);
+        problems += 1;
+    }
+
+    if problems > 0 {
+        println!("Error: Found {} problem(s) in Blocks {} to {}.", problems, from, to);
+        std::process::exit(1);
+    }
+    println!("Verified Blocks {} to {}. No problems found.", from, to);
+}
+
+// Check block returns a description of every check that `block` fails, given the header of the Block before it.
+pub(crate) fn check_block(prev: Option<&protocol_types::BlockHeader>, block: &protocol_types::Block) -> Vec<String> {
+    let header = &block.header;
+    let mut problems = vec![];
+
+    if let Some(prev) = prev {
+        if header.prev_block_hash != prev.this_block_hash {
+            problems.push(format!("prev_block_hash {} does not match the hash {} of Block {}",
+                encode(&header.prev_block_hash), encode(&prev.this_block_hash), prev.block_number));
+        }
+        if header.block_number != prev.block_number + 1 {
+            problems.push(format!("block_number does not follow Block {}", prev.block_number));
+        }
+    }
+
+    for tx in &block.transactions {
+        problems.extend(check_transaction(tx));
+    }
+
+    problems
+}
+
+// Check transaction returns a description of every problem with the signature and hash of `tx`.
+fn check_transaction(tx: &protocol_types::Transaction) -> Vec<String> {
+    let mut problems = vec![];
+    let hash = submit::transaction_hash(&tx.signature);
+    if tx.hash != hash {
+        problems.push(format!("Transaction {} does not match the hash {} of its signature", encode(&tx.hash), encode(&hash)));
+    }
+    let verified = ed25519_dalek::PublicKey::from_bytes(&tx.from_address).ok()
+        .zip(ed25519_dalek::Signature::from_bytes(&tx.signature).ok())
+        .map(|(public_key, signature)| public_key.verify(&submit::signing_bytes(tx), &signature).is_ok());
+    if verified != Some(true) {
+        problems.push(format!("Transaction {} signature does not verify against from_address {}", encode(&tx.hash), encode(&tx.from_address)));
+    }
+    problems
+}
+
+fn encode(bytes: &[u8]) -> String {
+    protocol_types::Base64URL::encode(bytes).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use protocol_types::Deserializable;
+
+    use crate::operations::{fixtures, submit};
+
+    use super::check_block;
+
+    fn keypair() -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7; 32]).unwrap();
+        ed25519_dalek::Keypair { public: ed25519_dalek::PublicKey::from(&secret), secret }
+    }
+
+    // Signed transaction signs a Transaction in the same way as `submit` does before sending it.
+    fn signed_transaction(keypair: &ed25519_dalek::Keypair, nonce: u64) -> protocol_types::Transaction {
+        let transaction = protocol_types::Transaction {
+            from_address: keypair.public.to_bytes(),
+            to_address: [2; 32],
+            value: 1,
+            gas_limit: 100000,
+            gas_price: 1,
+            n_txs_on_chain_from_address: nonce,
+            ..fixtures::transaction()
+        };
+        let keypair_base64 = protocol_types::Base64URL::encode(keypair.to_bytes()).to_string();
+        let (serialized, _) = submit::hash_and_sign_transaction(keypair_base64, transaction).unwrap();
+        protocol_types::Transaction::deserialize(&serialized).unwrap()
+    }
+
+    fn make_block(keypair: &ed25519_dalek::Keypair, block_number: u64, prev_block_hash: [u8; 32]) -> protocol_types::Block {
+        let mut block = fixtures::block(block_number, vec![signed_transaction(keypair, block_number)]);
+        block.header.prev_block_hash = prev_block_hash;
+        block.header.this_block_hash = [block_number as u8; 32];
+        block
+    }
+
+    #[test]
+    fn test_check_transaction() {
+        let keypair = keypair();
+        let transaction = signed_transaction(&keypair, 0);
+        assert!(super::check_transaction(&transaction).is_empty());
+
+        let mut tampered = signed_transaction(&keypair, 0);
+        tampered.value = 1000;
+        let problems = super::check_transaction(&tampered);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("signature does not verify"));
+
+        let mut rehashed = signed_transaction(&keypair, 0);
+        rehashed.hash = [9; 32];
+        let problems = super::check_transaction(&rehashed);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("hash"));
+    }
+
+    #[test]
+    fn test_check_block() {
+        let keypair = keypair();
+        let first = make_block(&keypair, 1, [0; 32]);
+        let second = make_block(&keypair, 2, first.header.this_block_hash);
+        assert!(check_block(None, &first).is_empty());
+        assert!(check_block(Some(&first.header), &second).is_empty());
+
+        let unlinked = make_block(&keypair, 2, [9; 32]);
+        let problems = check_block(Some(&first.header), &unlinked);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("prev_block_hash"));
+
+        let skipped = make_block(&keypair, 3, first.header.this_block_hash);
+        let problems = check_block(Some(&first.header), &skipped);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("block_number"));
+
+        let mut tampered = make_block(&keypair, 2, first.header.this_block_hash);
+        tampered.transactions[0].value = 1000;
+        let problems = check_block(Some(&first.header), &tampered);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("signature does not verify"));
+
+        assert!(!check_block(Some(&second.header), &first).is_empty());
+    }
+}
@@ -14,34 +14,23 @@
  You should have received a copy of the GNU General Public License
  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use crate::display_types;
+use crate::{display_types::{self, BytesEncoding, DecodedFrom, EncodedFrom}, file_format::FileFormat, input};
 
-use protocol_types::Deserializable;
-use std::path::Path;
+use protocol_types::{Deserializable, Serializable};
 
 
 
-pub fn parse_file(path_to_ptr: String) -> String {
+// Parse file displays the protocol type serialized in the file at `path_to_ptr`, with free-form bytes displayed
+// in `encoding`, or as JSON if `json` is set, with free-form bytes Base64URL encoded. Its
+// type is given by the data type extension of the file name (`<name>.<data type extension>.prt`), or by
+// `data_type`, which is required when the file is read from standard input (`-`).
+pub fn parse_file(path_to_ptr: String, data_type: Option<String>, json: bool, encoding: BytesEncoding) -> String {
 
-    let names: Vec<&str> = path_to_ptr.split(".").collect();
-    if names.len() < 3 {
-        println!("Error: : Invalid file name. File extension must be .<data type extension>.prt");
-        std::process::exit(1);
-    }
-    if names[names.len()-1] != "prt" {
-        println!("Error: : Invalid file extension. File extension must be .<data type extension>.prt");
-        std::process::exit(1);
-    }
-
-    let ext = names[names.len()-2];
-    
-    if !is_correct_data_type_ext(ext) {
-        println!("Error: : Not recognized data type extension");
-        std::process::exit(1);
-    };
+    let ext = data_type_ext(&path_to_ptr, data_type);
+    let ext = ext.as_str();
 
-    let file_binary = if Path::new(&path_to_ptr).is_file(){
-        match std::fs::read(&path_to_ptr) {
+    let file_binary = if input::exists(&path_to_ptr){
+        match input::read(&path_to_ptr) {
             Ok(data) => data,
             Err(e) => {
                 println!("Error: : Fail to read file although is file found {:?}", e);
@@ -53,50 +42,178 @@ pub fn parse_file(path_to_ptr: String) -> String {
         std::process::exit(1);
     };
 
-    to_display_types(&file_binary, ext)
+    if json {
+        to_display_json(&file_binary, ext)
+    } else {
+        to_display_types(&file_binary, ext, encoding)
+    }
+}
+
+// Encode file is the inverse of parse file. It serializes the protocol type displayed as JSON (or YAML, TOML) in
+// the file at `path_to_json` into the Prt file at `out`, or to standard output if `out` is `-`. Its type is given
+// by `data_type`, or else by the data type extension of `out`.
+pub fn encode_file(path_to_json: String, data_type: Option<String>, out: String) {
+    let ext = data_type_ext(&out, data_type);
+
+    let content = match input::read_to_string(&path_to_json) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("Error: : Cannot read file {}. {}", path_to_json, e);
+            std::process::exit(1);
+        }
+    };
+    let json_val = match FileFormat::detect(&path_to_json, &content).parse(&content) {
+        Ok(json_val) => json_val,
+        Err(e) => {
+            println!("Error: : {}", e);
+            std::process::exit(1);
+        }
+    };
+    let serialized_data = match from_display_types(json_val, &ext) {
+        Ok(serialized_data) => serialized_data,
+        Err(e) => {
+            println!("Error: : Cannot encode the file as data type `{}`. {}", ext, e);
+            std::process::exit(1);
+        }
+    };
+
+    if input::is_stdin(&out) {
+        use std::io::Write;
+        if let Err(e) = std::io::stdout().write_all(&serialized_data) {
+            println!("Error: : Cannot write to standard output. {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let out = prt_path(&out, None, &ext);
+    match std::fs::write(&out, serialized_data) {
+        Ok(_) => println!("Your Prt file is saved to `{}`", out),
+        Err(e) => {
+            println!("Error: : Unable to save Prt file to `{}`. {}", out, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Data type ext returns `data_type`, or else the data type extension in the name of the Prt file at `path`.
+fn data_type_ext(path: &str, data_type: Option<String>) -> String {
+    let ext = match data_type {
+        Some(data_type) => data_type,
+        None if input::is_stdin(path) => {
+            println!("Error: : 'type' is required when the Prt file is `-`.");
+            std::process::exit(1);
+        },
+        None => {
+            let names: Vec<&str> = path.split(".").collect();
+            if names.len() < 3 {
+                println!("Error: : Invalid file name. File extension must be .<data type extension>.prt");
+                std::process::exit(1);
+            }
+            if names[names.len()-1] != "prt" {
+                println!("Error: : Invalid file extension. File extension must be .<data type extension>.prt");
+                std::process::exit(1);
+            }
+            names[names.len()-2].to_string()
+        }
+    };
+
+    if !is_correct_data_type_ext(&ext) {
+        println!("Error: : Not recognized data type extension");
+        std::process::exit(1);
+    };
+    ext
 }
 
 fn is_correct_data_type_ext(ext: &str) -> bool {
     ["bh", "blk", "tx", "recp", "evt", "call", "mprf", "sprfs"].contains(&ext)
 }
 
-fn to_display_types(serialized_data: &Vec<u8>, ext: &str) -> String {
+// Prt path returns the path to save a Prt file of data type `ext` at `path`, so that `parse prt` recognizes it.
+// `.<ext>.prt` is appended unless `path` already ends with it. `label` (e.g. a Block number) tells apart the
+// files saved by one query, e.g. `blocks.12.blk.prt`.
+pub fn prt_path(path: &str, label: Option<&str>, ext: &str) -> String {
+    let base = path.strip_suffix(".prt").unwrap_or(path);
+    let base = base.strip_suffix(&format!(".{}", ext)).unwrap_or(base);
+    match label {
+        Some(label) => format!("{}.{}.{}.prt", base, label, ext),
+        None => format!("{}.{}.prt", base, ext),
+    }
+}
+
+// Maps every data type extension to its protocol type and display type, and expands `$convert` for the matching
+// extension. `$convert` is a macro called with the protocol type and the display type.
+macro_rules! match_data_type_ext {
+    ($ext:expr, $convert:ident) => {
+        match $ext {
+            "bh" => $convert!(protocol_types::BlockHeader, display_types::BlockHeader),
+            "blk" => $convert!(protocol_types::Block, display_types::Block),
+            "tx" => $convert!(protocol_types::Transaction, display_types::Transaction),
+            "recp" => $convert!(protocol_types::Receipt, display_types::Receipt),
+            "evt" => $convert!(protocol_types::Event, display_types::Event),
+            "call" => $convert!(protocol_types::CallData, display_types::CallData),
+            "mprf" => $convert!(protocol_types::MerkleProof, display_types::MerkleProof),
+            "sprfs" => $convert!(protocol_types::StateProofs, display_types::StateProofs),
+            _ => {}
+        }
+    };
+}
+
+// To display types returns the protocol type of data type `ext` serialized in `serialized_data` in human readable
+// form, or an empty string if it is not of that type.
+pub(crate) fn to_display_types(serialized_data: &Vec<u8>, ext: &str, encoding: BytesEncoding) -> String {
     macro_rules! from_prt_to_return_display_types {
-        ($ext:expr, $($e:expr, $m1:tt::$t1:tt => $m2:tt::$t2:tt)*) => {
-            match $ext {
-                $(
-                    $e => {
-                        if let Ok(value) = $m1::$t1::deserialize(&serialized_data) {
-                            return format!("{:?}", $m2::$t2::from(value));
-                        }
-                    }
-                )*
-
-                _=> {}
+        ($t1:ty, $t2:ty) => {
+            if let Ok(value) = <$t1>::deserialize(&serialized_data) {
+                return format!("{:?}", <$t2>::encoded_from(value, encoding));
             }
-            
         };
     }
 
-    from_prt_to_return_display_types!(ext,
-        "bh", protocol_types::BlockHeader => display_types::BlockHeader
-        "blk", protocol_types::Block => display_types::Block
-        "tx", protocol_types::Transaction => display_types::Transaction
-        "recp", protocol_types::Receipt => display_types::Receipt
-        "evt", protocol_types::Event => display_types::Event
-        "call", protocol_types::CallData => display_types::CallData
-        "mprf", protocol_types::MerkleProof => display_types::MerkleProof
-        "sprfs", protocol_types::StateProofs => display_types::StateProofs
-    );
+    match_data_type_ext!(ext, from_prt_to_return_display_types);
     
     "".to_string()
 }
 
+// Json bytes encoding is the encoding of free-form bytes in Prt files displayed as JSON.
+const JSON_BYTES_ENCODING: BytesEncoding = BytesEncoding::Base64;
+
+// To display json is the same as to display types, but returns the display type as JSON, which can be encoded
+// back with `from_display_types`. Free-form bytes are always Base64URL encoded, so that no bytes are lost.
+fn to_display_json(serialized_data: &Vec<u8>, ext: &str) -> String {
+    macro_rules! from_prt_to_return_display_json {
+        ($t1:ty, $t2:ty) => {
+            if let Ok(value) = <$t1>::deserialize(&serialized_data) {
+                return serde_json::to_string(&<$t2>::encoded_from(value, JSON_BYTES_ENCODING)).unwrap();
+            }
+        };
+    }
+
+    match_data_type_ext!(ext, from_prt_to_return_display_json);
+
+    "".to_string()
+}
+
+// From display types returns the serialized protocol type of data type `ext` displayed as `json_val`.
+fn from_display_types(json_val: serde_json::Value, ext: &str) -> Result<Vec<u8>, String> {
+    macro_rules! from_display_types_to_return_prt {
+        ($t1:ty, $t2:ty) => {{
+            let display_value: $t2 = serde_json::from_value(json_val).map_err(|e| e.to_string())?;
+            return Ok(<$t1>::decoded_from(display_value, JSON_BYTES_ENCODING)?.serialize());
+        }};
+    }
+
+    match_data_type_ext!(ext, from_display_types_to_return_prt);
+
+    Err(format!("Not recognized data type extension `{}`", ext))
+}
+
 
 #[cfg(test)]
 mod test {
     use protocol_types::Serializable;
 
+    use crate::display_types::BytesEncoding;
+
     #[test]
     fn test_to_display_types() {
         let block_header = protocol_types::BlockHeader {
@@ -113,8 +230,8 @@ mod test {
             signature : [8u8; 64],
         };
         let serialized_bh = protocol_types::BlockHeader::serialize(&block_header);
-        let output = super::to_display_types(&serialized_bh, "bh");
-        let result = r#"BlockHeader { blockchain_id: 9828192, block_version_number: 2, block_number: 1, timestamp: 3, prev_block_hash: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", this_block_hash: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI", txs_hash: "AQIDBAUGBwgJAAECAwQFBgcICQABAgMEBQYHCAkAAQI", state_hash: "BAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ", receipts_hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", proposer_public_key: "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }"#;
+        let output = super::to_display_types(&serialized_bh, "bh", BytesEncoding::Auto);
+        let result = r#"BlockHeader { blockchain_id: 9828192, block_version_number: 2, block_number: 1, timestamp: "1970-01-01T00:00:03Z", prev_block_hash: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", this_block_hash: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI", txs_hash: "AQIDBAUGBwgJAAECAwQFBgcICQABAgMEBQYHCAkAAQI", state_hash: "BAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ", receipts_hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", proposer_public_key: "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }"#;
         assert_eq!(output, result);
       
         let block = protocol_types::Block {
@@ -136,8 +253,8 @@ mod test {
             receipts: generate_receipts(),
         };
         let serialized_blk = protocol_types::Block::serialize(&block);
-        let output = super::to_display_types(&serialized_blk, "blk");
-        let result = r#"Block { header: BlockHeader { blockchain_id: 9828192, block_version_number: 2, block_number: 1, timestamp: 3, prev_block_hash: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", this_block_hash: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI", txs_hash: "AQIDBAUGBwgJAAECAwQFBgcICQABAgMEBQYHCAkAAQI", state_hash: "BAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ", receipts_hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", proposer_public_key: "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }, transactions: [Transaction { from_address: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", to_address: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", value: 1000, tip: 1, gas_limit: 100000, gas_price: 100000, data: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg", n_txs_on_chain_from_address: 0, hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }, Transaction { from_address: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", to_address: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", value: 1000, tip: 1, gas_limit: 100000, gas_price: 100000, data: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg", n_txs_on_chain_from_address: 0, hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }], receipts: [Receipt { status_code: Success, gas_consumed: 100, return_value: [], events: [Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }, Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }] }, Receipt { status_code: Success, gas_consumed: 100, return_value: [], events: [Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }, Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }] }] }"#;
+        let output = super::to_display_types(&serialized_blk, "blk", BytesEncoding::Auto);
+        let result = r#"Block { header: BlockHeader { blockchain_id: 9828192, block_version_number: 2, block_number: 1, timestamp: "1970-01-01T00:00:03Z", prev_block_hash: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", this_block_hash: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI", txs_hash: "AQIDBAUGBwgJAAECAwQFBgcICQABAgMEBQYHCAkAAQI", state_hash: "BAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ", receipts_hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", proposer_public_key: "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }, transactions: [Transaction { from_address: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", to_address: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", value: 1000, tip: 1, gas_limit: 100000, gas_price: 100000, data: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg", n_txs_on_chain_from_address: 0, hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }, Transaction { from_address: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", to_address: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", value: 1000, tip: 1, gas_limit: 100000, gas_price: 100000, data: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg", n_txs_on_chain_from_address: 0, hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }], receipts: [Receipt { status_code: Success, gas_consumed: 100, return_value: "", events: [Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }, Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }] }, Receipt { status_code: Success, gas_consumed: 100, return_value: "", events: [Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }, Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }] }] }"#;
 
         assert_eq!(output, result);
         
@@ -154,20 +271,20 @@ mod test {
             signature : [8u8; 64]
         };
         let serialized_tx = protocol_types::Transaction::serialize(&transaction);
-        let output = super::to_display_types(&serialized_tx, "tx");
+        let output = super::to_display_types(&serialized_tx, "tx", BytesEncoding::Auto);
         let result = r#"Transaction { from_address: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", to_address: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", value: 1000, tip: 1, gas_limit: 100000, gas_price: 100000, data: "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg", n_txs_on_chain_from_address: 0, hash: "BgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgY", signature: "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICA" }"#;
 
         assert_eq!(output, result);
 
         let receipt = protocol_types::Receipt {
             status_code: protocol_types::ReceiptStatusCode::Success,
-            gas_consumed: 100 as u64,
+            gas_consumed: 100_u64,
             return_value: vec![],
             events: generate_events()
         };
         let serialized_recp = protocol_types::Receipt::serialize(&receipt);
-        let output = super::to_display_types(&serialized_recp, "recp");
-        let result = r#"Receipt { status_code: Success, gas_consumed: 100, return_value: [], events: [Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }, Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }] }"#;
+        let output = super::to_display_types(&serialized_recp, "recp", BytesEncoding::Auto);
+        let result = r#"Receipt { status_code: Success, gas_consumed: 100, return_value: "", events: [Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }, Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }] }"#;
 
         assert_eq!(output, result);
 
@@ -178,7 +295,7 @@ mod test {
             value: vec![6,2,3]
         };
         let serialized_evt = protocol_types::Event::serialize(&event);
-        let output = super::to_display_types(&serialized_evt, "evt");
+        let output = super::to_display_types(&serialized_evt, "evt", BytesEncoding::Auto);
         let result = r#"Event { topic: "\n\u{14}\u{1e}(2<", value: "\u{6}\u{2}\u{3}" }"#;
         assert_eq!(output, result);
 
@@ -188,7 +305,7 @@ mod test {
             arguments:   [1u8; 34].to_vec()            
         };
         let serialized_call = protocol_types::CallData::serialize(&call);
-        let output = super::to_display_types(&serialized_call, "call");
+        let output = super::to_display_types(&serialized_call, "call", BytesEncoding::Auto);
         let result = r#"CallData { method_name: "call data", arguments: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ" }"#;
         assert_eq!(output, result);
 
@@ -201,7 +318,7 @@ mod test {
             proof :[1u8; 128].to_vec()
         };
         let serialized_mrpf = protocol_types::MerkleProof::serialize(&merkle_proof);
-        let output = super::to_display_types(&serialized_mrpf, "mprf");
+        let output = super::to_display_types(&serialized_mrpf, "mprf", BytesEncoding::Auto);
         let result = r#"MerkleProof { root_hash: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", total_leaves_count: 123, leaf_indices: [0, 4, 100], leaf_hashes: ["AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE"], proof: [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1] }"#;
         assert_eq!(output, result);
 
@@ -217,12 +334,135 @@ mod test {
             proof : vec![[1u8; 56].to_vec(), [1u8; 57].to_vec(), [1u8; 58].to_vec()]
         };
         let serialized_sprfs = protocol_types::StateProofs::serialize(&state_proof);
-        let output = super::to_display_types(&serialized_sprfs, "sprfs");
+        let output = super::to_display_types(&serialized_sprfs, "sprfs", BytesEncoding::Auto);
         let result = r#"StateProofs { root_hash: "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE", items: [([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1], Some([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1])), ([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1], None), ([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1], Some([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]))], proof: [[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1], [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1], [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]] }"#;
         assert_eq!(output, result);
 
     }
 
+    #[test]
+    fn test_non_utf8_event() {
+        let event = protocol_types::Event {
+            topic: vec![0xff, 0xfe, 0x00],
+            value: "value".as_bytes().to_vec()
+        };
+        let serialized_evt = protocol_types::Event::serialize(&event);
+        let output = super::to_display_types(&serialized_evt, "evt", BytesEncoding::Auto);
+        assert_eq!(output, r#"Event { topic: "__4A", value: "value" }"#);
+
+        assert_eq!(BytesEncoding::Hex.encode(&event.topic), "fffe00");
+        assert_eq!(BytesEncoding::Base64.encode(&event.value), "dmFsdWU");
+        assert_eq!(BytesEncoding::Utf8.encode(&event.topic), "\u{fffd}\u{fffd}\u{0}");
+        assert_eq!("HEX".parse::<BytesEncoding>(), Ok(BytesEncoding::Hex));
+        assert!("latin1".parse::<BytesEncoding>().is_err());
+    }
+
+    #[test]
+    fn test_from_display_types() {
+        // Bytes of Events and Receipts are not valid utf8, which must not be lost in JSON.
+        let receipt = protocol_types::Receipt {
+            status_code: protocol_types::ReceiptStatusCode::Reverted,
+            gas_consumed: 100,
+            return_value: vec![0xff, 0xfe, 0, 1],
+            events: vec![protocol_types::Event { topic: vec![0xff, 0xfe, 0], value: vec![0x80, b'v', 0xc3] }],
+        };
+        let block_header = protocol_types::BlockHeader {
+            blockchain_id: 9828192,
+            block_version_number: 2,
+            block_number: 1,
+            timestamp: 1660003200,
+            prev_block_hash: [1u8; 32],
+            this_block_hash: [2u8; 32],
+            txs_hash: [3u8; 32],
+            state_hash: [4u8; 32],
+            receipts_hash: [6u8; 32],
+            proposer_public_key: [7u8; 32],
+            signature: [8u8; 64],
+        };
+        let block = protocol_types::Block {
+            header: block_header.clone(),
+            transactions: generate_transactions(),
+            receipts: vec![receipt.clone(), receipt.clone()],
+        };
+        let call = protocol_types::CallData { method_name: "transfer".to_string(), arguments: vec![1u8; 34] };
+        let merkle_proof = protocol_types::MerkleProof {
+            root_hash: [1u8; 32],
+            total_leaves_count: 123,
+            leaf_indices: vec![0, 4],
+            leaf_hashes: vec![[2u8; 32], [3u8; 32]],
+            proof: vec![1u8; 10],
+        };
+        let state_proof = protocol_types::StateProofs {
+            root_hash: [1u8; 32],
+            items: vec![(vec![1u8; 3], Some(vec![2u8; 4])), (vec![3u8; 2], None)],
+            proof: vec![vec![1u8; 5]],
+        };
+
+        let serialized = vec![
+            ("bh", block_header.serialize()),
+            ("blk", block.serialize()),
+            ("tx", generate_transactions()[0].serialize()),
+            ("recp", receipt.serialize()),
+            ("evt", receipt.events[0].serialize()),
+            ("call", call.serialize()),
+            ("mprf", merkle_proof.serialize()),
+            ("sprfs", state_proof.serialize()),
+        ];
+        for (ext, serialized_data) in serialized {
+            let json_val = serde_json::from_str(&super::to_display_json(&serialized_data, ext)).unwrap();
+            assert_eq!(super::from_display_types(json_val, ext), Ok(serialized_data), "data type {}", ext);
+        }
+
+        use protocol_types::ReceiptStatusCode::*;
+        for status_code in [Success, WrongNonce, NotEnoughBalanceForGasLimit, NotEnoughBalanceForTransfer, PreExecutionGasExhausted,
+            DisallowedOpcode, CannotCompile, NoExportedContractMethod, OtherDeployError, ExecutionProperGasExhausted, Reverted,
+            InternalExecutionProperGasExhaustion, InternalReverted, Else] {
+            let name = format!("{:?}", status_code);
+            let serialized_data = protocol_types::Receipt { status_code, ..receipt.clone() }.serialize();
+            let json_val = serde_json::from_str(&super::to_display_json(&serialized_data, "recp")).unwrap();
+            assert_eq!(super::from_display_types(json_val, "recp"), Ok(serialized_data), "status code {}", name);
+        }
+
+        let invalid_tx = serde_json::json!({
+            "from_address": "AQEB", "to_address": "AQEB", "value": 1, "tip": 0, "gas_limit": 1, "gas_price": 1, "data": "",
+            "n_txs_on_chain_from_address": 0, "hash": "AQEB", "signature": "AQEB",
+        });
+        assert!(super::from_display_types(invalid_tx, "tx").unwrap_err().contains("'from_address' must be 32 bytes"));
+        let unknown_status = serde_json::json!({ "status_code": "Unknown", "gas_consumed": 1, "return_value": "", "events": [] });
+        assert!(super::from_display_types(unknown_status, "recp").unwrap_err().contains("unknown status code"));
+
+        // The timestamp of a header is an RFC3339 datetime or a Unix timestamp, never relative to now.
+        let mut header_json: serde_json::Value = serde_json::from_str(&super::to_display_json(&block_header.serialize(), "bh")).unwrap();
+        header_json["timestamp"] = serde_json::json!("1660003200");
+        assert_eq!(super::from_display_types(header_json.clone(), "bh"), Ok(block_header.serialize()));
+        header_json["timestamp"] = serde_json::json!("now");
+        assert!(super::from_display_types(header_json, "bh").unwrap_err().contains("'timestamp' is invalid"));
+    }
+
+    #[test]
+    fn test_decode_bytes_encoding() {
+        let bytes = vec![0xff, 0xfe, 0x00, 0x41];
+        for encoding in &[BytesEncoding::Hex, BytesEncoding::Base64] {
+            assert_eq!(encoding.decode(&encoding.encode(&bytes)), Ok(bytes.clone()));
+        }
+        assert_eq!(BytesEncoding::Utf8.decode("value"), Ok(b"value".to_vec()));
+        assert_eq!(BytesEncoding::Auto.decode(""), Ok(vec![]));
+        assert!(BytesEncoding::Auto.decode("value").is_err());
+        assert!(BytesEncoding::Hex.decode("fff").is_err());
+        assert!(BytesEncoding::Hex.decode("zz").is_err());
+        assert!(BytesEncoding::Hex.decode("+f").is_err());
+    }
+
+    #[test]
+    fn test_prt_path() {
+        assert_eq!(super::prt_path("proof", None, "mprf"), "proof.mprf.prt");
+        assert_eq!(super::prt_path("proof.prt", None, "mprf"), "proof.mprf.prt");
+        assert_eq!(super::prt_path("./out/proof.mprf.prt", None, "mprf"), "./out/proof.mprf.prt");
+        assert_eq!(super::prt_path("blocks.blk.prt", Some("12"), "blk"), "blocks.12.blk.prt");
+        assert_eq!(super::prt_path("txs", Some("3"), "recp"), "txs.3.recp.prt");
+        assert_eq!(super::prt_path("state.tx.prt", None, "sprfs"), "state.tx.sprfs.prt");
+    }
+
     fn generate_events() -> Vec<protocol_types::Event> {
 
         let mut ret = vec![];
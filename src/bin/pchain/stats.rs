@@ -0,0 +1,145 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+// Stats module summarizes series of numbers computed by the analyze commands, as statistics or as text charts.
+
+use std::fmt;
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "min {}, p50 {}, p90 {}, p95 {}, p99 {}, max {}, mean {:.2} (n = {})",
+            self.min, self.p50, self.p90, self.p95, self.p99, self.max, self.mean, self.count)
+    }
+}
+
+/// Distribution of `values`, or None if there are none.
+pub fn distribution(values: &[f64]) -> Option<Distribution> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    Some(Distribution {
+        count: sorted.len(),
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        p50: percentile(&sorted, 50.0),
+        p90: percentile(&sorted, 90.0),
+        p95: percentile(&sorted, 95.0),
+        p99: percentile(&sorted, 99.0),
+    })
+}
+
+/// Nearest-rank percentile of non-empty, ascending `sorted` values, i.e. the smallest value
+/// which is greater than or equal to `p` percent of the values.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Resample `values` to at most `width` values, averaging consecutive values into buckets.
+pub fn resample(values: &[f64], width: usize) -> Vec<f64> {
+    if width == 0 || values.len() <= width {
+        return values.to_vec();
+    }
+    (0..width).map(|i| {
+        let bucket = &values[i * values.len() / width..(i + 1) * values.len() / width];
+        bucket.iter().sum::<f64>() / bucket.len() as f64
+    }).collect()
+}
+
+/// Sparkline renders `values` as a single line of block characters, scaled between their min and max.
+pub fn sparkline(values: &[f64]) -> String {
+    let (min, max) = min_max(values);
+    values.iter().map(|v| {
+        let level = if max > min { ((v - min) / (max - min) * (SPARK_LEVELS.len() - 1) as f64).round() as usize } else { 0 };
+        SPARK_LEVELS[level]
+    }).collect()
+}
+
+/// Line chart renders `values` as `height` rows of ASCII text, one column per value, with the
+/// value of the top and bottom rows on the left axis.
+pub fn line_chart(values: &[f64], height: usize) -> Vec<String> {
+    let height = height.max(2);
+    let (min, max) = min_max(values);
+    let row_of = |v: f64| if max > min { ((v - min) / (max - min) * (height - 1) as f64).round() as usize } else { 0 };
+    let label_width = format!("{}", max).len().max(format!("{}", min).len());
+    (0..height).rev().map(|row| {
+        let label = if row == height - 1 { format!("{}", max) } else if row == 0 { format!("{}", min) } else { String::new() };
+        let line: String = values.iter().map(|v| if row_of(*v) == row { '*' } else { ' ' }).collect();
+        format!("{:>width$} |{}", label, line.trim_end(), width = label_width)
+    }).collect()
+}
+
+fn min_max(values: &[f64]) -> (f64, f64) {
+    values.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)))
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        assert_eq!(super::percentile(&sorted, 50.0), 50.0);
+        assert_eq!(super::percentile(&sorted, 95.0), 95.0);
+        assert_eq!(super::percentile(&sorted, 0.0), 1.0);
+        assert_eq!(super::percentile(&sorted, 100.0), 100.0);
+        assert_eq!(super::percentile(&[7.0], 99.0), 7.0);
+        assert_eq!(super::percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), 2.0);
+    }
+
+    #[test]
+    fn test_distribution() {
+        assert_eq!(super::distribution(&[]), None);
+        let distribution = super::distribution(&[3.0, 1.0, 2.0, 10.0]).unwrap();
+        assert_eq!(distribution.count, 4);
+        assert_eq!(distribution.min, 1.0);
+        assert_eq!(distribution.max, 10.0);
+        assert_eq!(distribution.mean, 4.0);
+        assert_eq!(distribution.p50, 2.0);
+        assert_eq!(distribution.p95, 10.0);
+    }
+
+    #[test]
+    fn test_charts() {
+        assert_eq!(super::sparkline(&[1.0, 8.0, 4.5]), "▁█▅");
+        assert_eq!(super::sparkline(&[3.0, 3.0]), "▁▁");
+        assert_eq!(super::resample(&[1.0, 3.0, 5.0, 7.0], 2), vec![2.0, 6.0]);
+        assert_eq!(super::resample(&[1.0, 3.0], 5), vec![1.0, 3.0]);
+        assert_eq!(super::line_chart(&[0.0, 10.0, 5.0], 3), vec![
+            "10 | *".to_string(),
+            "   |  *".to_string(),
+            " 0 |*".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,197 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+// Time parser reads points in time and durations given on the command line, and formats Unix
+// timestamps for display.
+//
+// A point in time is one of:
+// - a Unix timestamp in seconds, e.g. `1660000000`,
+// - an RFC3339 datetime, e.g. `2022-08-09T00:00:00Z` or `2022-08-09T08:00:00+08:00`,
+// - `now`, or a duration before now, e.g. `24h` or `24h ago`.
+// A duration is a number of seconds, or a sequence of numbers with units s, m, h, d or w, e.g. `5m` or `1h30m`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+
+/// Current Unix timestamp in seconds.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Parse a point in time into a Unix timestamp. Relative expressions are taken back from `now`.
+pub fn parse_time(time: &str, now: u64) -> Result<u64, String> {
+    let time = time.trim();
+    if time.eq_ignore_ascii_case("now") {
+        return Ok(now);
+    }
+    if let Some(timestamp) = parse_absolute_time(time) {
+        return timestamp;
+    }
+    let relative = time.strip_suffix("ago").unwrap_or(time).trim();
+    match parse_duration(relative.strip_prefix('-').unwrap_or(relative)) {
+        Ok(duration) => now.checked_sub(duration).ok_or_else(|| format!("`{}` is before 1970", time)),
+        Err(_) => Err(format!("cannot read time `{}`. Expected a Unix timestamp, an RFC3339 datetime (e.g. 2022-08-09T00:00:00Z), `now`, or a duration before now (e.g. 24h)", time)),
+    }
+}
+
+/// Parse a point in time given as a Unix timestamp or an RFC3339 datetime. Unlike `parse_time`, the result
+/// does not depend on the current time.
+pub fn parse_timestamp(time: &str) -> Result<u64, String> {
+    let time = time.trim();
+    parse_absolute_time(time).unwrap_or_else(|| Err(format!("cannot read time `{}`. Expected a Unix timestamp or an RFC3339 datetime (e.g. 2022-08-09T00:00:00Z)", time)))
+}
+
+// Parse absolute time returns None if `time` is neither a Unix timestamp nor an RFC3339 datetime.
+fn parse_absolute_time(time: &str) -> Option<Result<u64, String>> {
+    if !time.is_empty() && time.chars().all(|c| c.is_ascii_digit()) {
+        return Some(time.parse().map_err(|_| format!("timestamp `{}` is out of range", time)));
+    }
+    let datetime = DateTime::parse_from_rfc3339(time).ok()?;
+    let timestamp = datetime.timestamp();
+    if timestamp < 0 {
+        return Some(Err(format!("datetime `{}` is before 1970", time)));
+    }
+    Some(Ok(timestamp as u64))
+}
+
+/// Parse a duration into seconds.
+pub fn parse_duration(duration: &str) -> Result<u64, String> {
+    let duration = duration.trim();
+    let invalid = || format!("cannot read duration `{}`. Expected seconds, or numbers with units s, m, h, d or w (e.g. 5m, 1h30m)", duration);
+    if duration.is_empty() {
+        return Err(invalid());
+    }
+    if duration.chars().all(|c| c.is_ascii_digit()) {
+        return duration.parse().map_err(|_| invalid());
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in duration.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        total = value.checked_mul(unit).and_then(|secs| total.checked_add(secs)).ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Parse and validate the time range and moving window of an analytics query. Returns
+/// (start time, end time, window size, step size) in seconds.
+pub fn parse_window(start_time: &str, end_time: &str, window_size: &str, step_size: &str) -> Result<(u64, u64, u64, u64), String> {
+    let now = now();
+    let start_time = parse_time(start_time, now)?;
+    let end_time = parse_time(end_time, now)?;
+    let window_size = parse_duration(window_size)?;
+    let step_size = parse_duration(step_size)?;
+    if start_time >= end_time {
+        return Err(format!("start time {} is not before end time {}", format_timestamp(start_time), format_timestamp(end_time)));
+    }
+    if window_size == 0 || step_size == 0 {
+        return Err("window size and step size must be greater than zero".to_string());
+    }
+    if step_size > window_size {
+        return Err(format!("step size ({}s) is greater than window size ({}s)", step_size, window_size));
+    }
+    Ok((start_time, end_time, window_size, step_size))
+}
+
+/// Same as `parse_window`, but prints the error and exits for arguments which cannot be parsed.
+pub fn parse_window_or_exit(start_time: &str, end_time: &str, window_size: &str, step_size: &str) -> (u64, u64, u64, u64) {
+    match parse_window(start_time, end_time, window_size, step_size) {
+        Ok(window) => window,
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Format a Unix timestamp as an RFC3339 (ISO 8601) datetime in UTC, e.g. `2022-08-09T00:00:00Z`.
+pub fn format_timestamp(timestamp: u64) -> String {
+    match Utc.timestamp_opt(timestamp as i64, 0).single() {
+        Some(datetime) => datetime.to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => timestamp.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_parse_time() {
+        let now = 1_660_000_000;
+        assert_eq!(super::parse_time("now", now), Ok(now));
+        assert_eq!(super::parse_time("1650000000", now), Ok(1_650_000_000));
+        assert_eq!(super::parse_time("2022-08-09T00:00:00Z", now), Ok(1_660_003_200));
+        assert_eq!(super::parse_time("2022-08-09T08:00:00+08:00", now), Ok(1_660_003_200));
+        assert_eq!(super::parse_time("24h", now), Ok(now - 86_400));
+        assert_eq!(super::parse_time("1h30m ago", now), Ok(now - 5_400));
+        assert_eq!(super::parse_time("-5m", now), Ok(now - 300));
+        assert!(super::parse_time("yesterday", now).is_err());
+        assert!(super::parse_time("100000w", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(super::parse_timestamp("1650000000"), Ok(1_650_000_000));
+        assert_eq!(super::parse_timestamp("2022-08-09T08:00:00+08:00"), Ok(1_660_003_200));
+        assert!(super::parse_timestamp("now").is_err());
+        assert!(super::parse_timestamp("24h").is_err());
+        assert!(super::parse_timestamp("1969-12-31T23:59:59Z").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(super::parse_duration("300"), Ok(300));
+        assert_eq!(super::parse_duration("5m"), Ok(300));
+        assert_eq!(super::parse_duration("1h30m"), Ok(5_400));
+        assert_eq!(super::parse_duration("1d"), Ok(86_400));
+        assert_eq!(super::parse_duration("2w"), Ok(1_209_600));
+        assert!(super::parse_duration("5").is_ok());
+        assert!(super::parse_duration("m5").is_err());
+        assert!(super::parse_duration("5x").is_err());
+        assert!(super::parse_duration("").is_err());
+    }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(super::parse_window("1000", "2000", "5m", "1m"), Ok((1000, 2000, 300, 60)));
+        assert!(super::parse_window("2000", "1000", "5m", "1m").unwrap_err().contains("not before"));
+        assert!(super::parse_window("1000", "2000", "1m", "5m").unwrap_err().contains("greater than window"));
+        assert!(super::parse_window("1000", "2000", "0", "0").is_err());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(super::format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(super::format_timestamp(1_660_003_200), "2022-08-09T00:00:00Z");
+    }
+}
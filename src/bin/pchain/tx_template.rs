@@ -0,0 +1,147 @@
+/*
+ Copyright (c) 2022 ParallelChain Lab
+
+ This program is free software: you can redistribute it and/or modify
+ it under the terms of the GNU General Public License as published by
+ the Free Software Foundation, either version 3 of the License, or
+ (at your option) any later version.
+
+ This program is distributed in the hope that it will be useful,
+ but WITHOUT ANY WARRANTY; without even the implied warranty of
+ MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ GNU General Public License for more details.
+
+ You should have received a copy of the GNU General Public License
+ along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+// Tx template module renders Transaction files containing placeholders, so that one file can be
+// reused with different values. Values are escaped as the content of a double quoted string, which is
+// written the same way in JSON, YAML and TOML, so string values are quoted in the template, e.g.
+// `"to_address": "${key:treasury.address}"`. A value can therefore not add fields to the file. Defaults
+// are part of the template, and are not escaped.
+//
+// Placeholders are:
+// - `${NAME}`: the value of variable NAME, given with `--var NAME=value` or else as an environment variable.
+// - `${NAME:-default}`: as above, but `default` if the variable is not set.
+// - `${key:path.to.field}`: a field of the address book, e.g. `${key:treasury.address}`.
+// - `${nonce:auto}`: the on-chain nonce of the "from_address" of the rendered Transaction.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+// Variables given on the command line, which take precedence over environment variables.
+pub type TemplateVars = HashMap<String, String>;
+
+pub const AUTO_NONCE: &str = "nonce:auto";
+
+// Parse vars reads `--var` arguments of the form `NAME=value`.
+pub fn parse_vars(args: &[String]) -> Result<TemplateVars, String> {
+    args.iter().map(|arg| match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.to_string())),
+        _ => Err(format!("'var' must be of the form NAME=value, but got `{}`", arg)),
+    }).collect()
+}
+
+// Render replaces every placeholder in `template`. `resolve` is called with the reference of each
+// placeholder (without its default), and returns None if it is not set. Values are escaped, see `escape`.
+pub fn render<F: FnMut(&str) -> Result<Option<String>, String>>(template: &str, mut resolve: F) -> Result<String, String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| format!("placeholder `{}` is not closed with `}}`", &rest[start..]))?;
+        let body = &rest[start + 2..start + end];
+        let (reference, default) = match body.split_once(":-") {
+            Some((reference, default)) => (reference.trim(), Some(default)),
+            None => (body.trim(), None),
+        };
+        if reference.is_empty() {
+            return Err(format!("placeholder `${{{}}}` is empty", body));
+        }
+        match (resolve(reference)?, default) {
+            (Some(value), _) => rendered.push_str(&escape(&value)),
+            (None, Some(default)) => rendered.push_str(default),
+            (None, None) => return Err(format!("`{}` is not set. Give it with --var {}=<value>, as an environment variable, or with a default `${{{}:-<value>}}`", reference, reference, reference)),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+// Escape returns `value` as the content of a double quoted string. JSON, YAML and TOML double quoted
+// strings all accept the escapes of JSON: `\"`, `\\`, `\n`, `\r`, `\t`, `\b`, `\f` and `\uXXXX`.
+pub fn escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+// Lookup returns the string or number at the dot separated `path` in the address book.
+pub fn lookup(address_book: &Value, path: &str) -> Option<String> {
+    let value = path.split('.').try_fold(address_book, |value, field| value.get(field))?;
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::file_format::FileFormat;
+
+    fn resolve(name: &str) -> Result<Option<String>, String> {
+        let values: HashMap<&str, &str> = vec![("AMOUNT", "100"), ("key:treasury.address", "AgIC")].into_iter().collect();
+        Ok(values.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(super::render(r#"{"value": ${AMOUNT}, "to": "${key:treasury.address}"}"#, resolve),
+            Ok(r#"{"value": 100, "to": "AgIC"}"#.to_string()));
+        assert_eq!(super::render("${TIP:-5} ${AMOUNT:-1}", resolve), Ok("5 100".to_string()));
+        assert_eq!(super::render("${EMPTY:-}", resolve), Ok("".to_string()));
+        assert_eq!(super::render("no placeholders $ {}", resolve), Ok("no placeholders $ {}".to_string()));
+        assert!(super::render("${MISSING}", resolve).unwrap_err().contains("MISSING"));
+        assert!(super::render("${AMOUNT", resolve).is_err());
+        assert!(super::render("${}", resolve).is_err());
+    }
+
+    #[test]
+    fn test_render_escapes_values() {
+        let injected = |_: &str| Ok(Some("AgIC\", \"value\": 1000000, \"x\": \"\n".to_string()));
+        let json = super::render(r#"{"to_address": "${TO}", "value": 1}"#, injected).unwrap();
+        let yaml = super::render("to_address: \"${TO}\"\nvalue: 1", injected).unwrap();
+        let toml = super::render("to_address = \"${TO}\"\nvalue = 1", injected).unwrap();
+        let expected = serde_json::json!({ "to_address": "AgIC\", \"value\": 1000000, \"x\": \"\n", "value": 1 });
+        assert_eq!(FileFormat::Json.parse(&json), Ok(expected.clone()));
+        assert_eq!(FileFormat::Yaml.parse(&yaml), Ok(expected.clone()));
+        assert_eq!(FileFormat::Toml.parse(&toml), Ok(expected));
+
+        // Defaults are written by the author of the template, and are not escaped.
+        assert_eq!(super::render("${MISSING:-\"a\"}", resolve), Ok("\"a\"".to_string()));
+        assert_eq!(super::escape("a\\b\t\u{1}"), "a\\\\b\\t\\u0001");
+    }
+
+    #[test]
+    fn test_parse_vars() {
+        let vars = super::parse_vars(&["A=1".to_string(), "B=x=y".to_string()]).unwrap();
+        assert_eq!(vars.get("A").map(String::as_str), Some("1"));
+        assert_eq!(vars.get("B").map(String::as_str), Some("x=y"));
+        assert!(super::parse_vars(&["A".to_string()]).is_err());
+        assert!(super::parse_vars(&["=1".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let book = serde_json::json!({ "treasury": { "address": "AgIC", "limit": 5 }, "ops": "AQEB" });
+        assert_eq!(super::lookup(&book, "treasury.address"), Some("AgIC".to_string()));
+        assert_eq!(super::lookup(&book, "treasury.limit"), Some("5".to_string()));
+        assert_eq!(super::lookup(&book, "ops"), Some("AQEB".to_string()));
+        assert_eq!(super::lookup(&book, "treasury"), None);
+        assert_eq!(super::lookup(&book, "payroll.address"), None);
+    }
+}